@@ -0,0 +1,945 @@
+/*
+0xFF10 - 0xFF14: Channel 1 (square with frequency sweep): NR10 - NR14.
+0xFF16 - 0xFF19: Channel 2 (square): NR21 - NR24.
+0xFF1A - 0xFF1E: Channel 3 (wave): NR30 - NR34.
+0xFF20 - 0xFF23: Channel 4 (noise): NR41 - NR44.
+0xFF24: NR50 - master volume for the left/right outputs.
+0xFF25: NR51 - per-channel left/right panning.
+0xFF26: NR52 - power control and channel status.
+0xFF30 - 0xFF3F: Wave pattern RAM (32 4-bit samples).
+*/
+
+use std::io;
+
+use crate::{model::Model, wav::AudioRecorder};
+
+// output sample rate before any host resampling: one stereo sample every 64 T-cycles
+pub const SAMPLE_RATE: u32 = 4_194_304 / CYCLES_PER_SAMPLE;
+const CYCLES_PER_SAMPLE: u32 = 64;
+
+// the frame sequencer runs at 512 Hz
+const CYCLES_PER_FRAME_STEP: u32 = 8192;
+
+// per-sample decay of the output high-pass filter that removes the DAC's DC offset
+const HIGH_PASS_CHARGE: f32 = 0.997_315;
+
+// keeps about a second of audio if nobody is draining the buffer
+const MAX_BUFFERED_SAMPLES: usize = SAMPLE_RATE as usize * 2;
+
+const DUTY_PATTERNS: [[u8; 8]; 4] = [
+    [0, 0, 0, 0, 0, 0, 0, 1], // 12.5%
+    [1, 0, 0, 0, 0, 0, 0, 1], // 25%
+    [1, 0, 0, 0, 0, 1, 1, 1], // 50%
+    [0, 1, 1, 1, 1, 1, 1, 0], // 75%
+];
+
+const NOISE_DIVISORS: [u32; 8] = [8, 16, 32, 48, 64, 80, 96, 112];
+
+// bits that always read back as 1 for 0xFF10 - 0xFF2F
+const READ_MASKS: [u8; 0x20] = [
+    0x80, 0x3F, 0x00, 0xFF, 0xBF, // NR10 - NR14
+    0xFF, 0x3F, 0x00, 0xFF, 0xBF, // unused, NR21 - NR24
+    0x7F, 0xFF, 0x9F, 0xFF, 0xBF, // NR30 - NR34
+    0xFF, 0xFF, 0x00, 0x00, 0xBF, // unused, NR41 - NR44
+    0x00, 0x00, 0x70, // NR50 - NR52
+    0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, 0xFF, // unused
+];
+
+struct LengthCounter {
+    enabled: bool,
+    counter: u16,
+    max: u16,
+}
+
+impl LengthCounter {
+    fn new(max: u16) -> LengthCounter {
+        LengthCounter {
+            enabled: false,
+            counter: 0,
+            max,
+        }
+    }
+
+    fn load(&mut self, value: u16) {
+        self.counter = self.max - value;
+    }
+
+    // returns true when the counter expires and the channel should turn off
+    fn clock(&mut self) -> bool {
+        if self.enabled && self.counter > 0 {
+            self.counter -= 1;
+            return self.counter == 0;
+        }
+        false
+    }
+
+    // handles an NRx4 write, returns true if the extra length clock disabled the channel.
+    // enabling the counter during a frame sequencer step that doesn't clock length
+    // clocks it once more, and triggering reloads an expired counter
+    fn write_control(&mut self, value: u8, first_half: bool) -> bool {
+        let was_enabled = self.enabled;
+        self.enabled = value & 0x40 != 0;
+        let trigger = value & 0x80 != 0;
+
+        let mut disable = false;
+        if !was_enabled && self.enabled && first_half && self.counter > 0 {
+            self.counter -= 1;
+            disable = self.counter == 0 && !trigger;
+        }
+
+        if trigger && self.counter == 0 {
+            self.counter = self.max;
+            if self.enabled && first_half {
+                self.counter -= 1;
+            }
+        }
+
+        disable
+    }
+}
+
+struct Envelope {
+    initial_volume: u8,
+    increase: bool,
+    period: u8,
+    volume: u8,
+    timer: u8,
+}
+
+impl Envelope {
+    fn new() -> Envelope {
+        Envelope {
+            initial_volume: 0,
+            increase: false,
+            period: 0,
+            volume: 0,
+            timer: 0,
+        }
+    }
+
+    fn write(&mut self, value: u8) {
+        self.initial_volume = value >> 4;
+        self.increase = value & 0x08 != 0;
+        self.period = value & 0x07;
+    }
+
+    fn trigger(&mut self) {
+        self.volume = self.initial_volume;
+        self.timer = if self.period == 0 { 8 } else { self.period };
+    }
+
+    fn clock(&mut self) {
+        if self.period == 0 {
+            return;
+        }
+
+        self.timer = self.timer.saturating_sub(1);
+        if self.timer == 0 {
+            self.timer = self.period;
+            if self.increase && self.volume < 15 {
+                self.volume += 1;
+            } else if !self.increase && self.volume > 0 {
+                self.volume -= 1;
+            }
+        }
+    }
+}
+
+struct Sweep {
+    period: u8,
+    negate: bool,
+    shift: u8,
+    timer: u8,
+    enabled: bool,
+    shadow: u16,
+    // a negate-mode calculation happened since the last trigger
+    negated: bool,
+}
+
+impl Sweep {
+    fn new() -> Sweep {
+        Sweep {
+            period: 0,
+            negate: false,
+            shift: 0,
+            timer: 0,
+            enabled: false,
+            shadow: 0,
+            negated: false,
+        }
+    }
+
+    // returns false if switching out of negate mode after a negate calculation kills the channel
+    fn write(&mut self, value: u8) -> bool {
+        self.period = (value >> 4) & 0x07;
+        self.negate = value & 0x08 != 0;
+        self.shift = value & 0x07;
+
+        !self.negated || self.negate
+    }
+
+    fn calculate(&mut self) -> u16 {
+        let delta = self.shadow >> self.shift;
+        if self.negate {
+            self.negated = true;
+            self.shadow.wrapping_sub(delta)
+        } else {
+            self.shadow + delta
+        }
+    }
+
+    fn reload_timer(&mut self) {
+        self.timer = if self.period == 0 { 8 } else { self.period };
+    }
+}
+
+struct SquareChannel {
+    enabled: bool,
+    dac_enabled: bool,
+    duty: u8,
+    duty_step: u8,
+    frequency: u16,
+    timer: i32,
+    length: LengthCounter,
+    envelope: Envelope,
+    sweep: Option<Sweep>,
+}
+
+impl SquareChannel {
+    fn new(with_sweep: bool) -> SquareChannel {
+        SquareChannel {
+            enabled: false,
+            dac_enabled: false,
+            duty: 0,
+            duty_step: 0,
+            frequency: 0,
+            timer: 0,
+            length: LengthCounter::new(64),
+            envelope: Envelope::new(),
+            sweep: if with_sweep { Some(Sweep::new()) } else { None },
+        }
+    }
+
+    fn period(&self) -> i32 {
+        (2048 - self.frequency as i32) * 4
+    }
+
+    fn step(&mut self, cycles: u32) {
+        self.timer -= cycles as i32;
+        while self.timer <= 0 {
+            self.timer += self.period();
+            self.duty_step = (self.duty_step + 1) & 0x07;
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        DUTY_PATTERNS[self.duty as usize][self.duty_step as usize] * self.envelope.volume
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        self.timer = self.period();
+        self.envelope.trigger();
+
+        let frequency = self.frequency;
+        if let Some(sweep) = self.sweep.as_mut() {
+            sweep.shadow = frequency;
+            sweep.negated = false;
+            sweep.reload_timer();
+            sweep.enabled = sweep.period != 0 || sweep.shift != 0;
+            if sweep.shift != 0 && sweep.calculate() > 2047 {
+                self.enabled = false;
+            }
+        }
+    }
+
+    fn clock_sweep(&mut self) {
+        let Some(sweep) = self.sweep.as_mut() else {
+            return;
+        };
+
+        sweep.timer = sweep.timer.saturating_sub(1);
+        if sweep.timer > 0 {
+            return;
+        }
+        sweep.reload_timer();
+
+        if !sweep.enabled || sweep.period == 0 {
+            return;
+        }
+
+        let frequency = sweep.calculate();
+        if frequency > 2047 {
+            self.enabled = false;
+        } else if sweep.shift != 0 {
+            sweep.shadow = frequency;
+            self.frequency = frequency;
+            // the new frequency is checked for overflow again but not written back
+            if sweep.calculate() > 2047 {
+                self.enabled = false;
+            }
+        }
+    }
+}
+
+struct WaveChannel {
+    enabled: bool,
+    dac_enabled: bool,
+    volume_code: u8,
+    frequency: u16,
+    timer: i32,
+    position: u8,
+    sample: u8,
+    length: LengthCounter,
+}
+
+impl WaveChannel {
+    fn new() -> WaveChannel {
+        WaveChannel {
+            enabled: false,
+            dac_enabled: false,
+            volume_code: 0,
+            frequency: 0,
+            timer: 0,
+            position: 0,
+            sample: 0,
+            length: LengthCounter::new(256),
+        }
+    }
+
+    fn period(&self) -> i32 {
+        (2048 - self.frequency as i32) * 2
+    }
+
+    fn step(&mut self, cycles: u32, wave_ram: &[u8]) {
+        self.timer -= cycles as i32;
+        while self.timer <= 0 {
+            self.timer += self.period();
+            self.position = (self.position + 1) & 0x1F;
+            let byte = wave_ram[(self.position / 2) as usize];
+            self.sample = if self.position & 1 == 0 {
+                byte >> 4
+            } else {
+                byte & 0x0F
+            };
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        match self.volume_code {
+            0 => 0,
+            code => self.sample >> (code - 1),
+        }
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        // the first sample is delayed slightly after triggering
+        self.timer = self.period() + 6;
+        self.position = 0;
+    }
+}
+
+struct NoiseChannel {
+    enabled: bool,
+    dac_enabled: bool,
+    clock_shift: u8,
+    short_mode: bool,
+    divisor_code: u8,
+    timer: i32,
+    lfsr: u16,
+    length: LengthCounter,
+    envelope: Envelope,
+}
+
+impl NoiseChannel {
+    fn new() -> NoiseChannel {
+        NoiseChannel {
+            enabled: false,
+            dac_enabled: false,
+            clock_shift: 0,
+            short_mode: false,
+            divisor_code: 0,
+            timer: 0,
+            lfsr: 0x7FFF,
+            length: LengthCounter::new(64),
+            envelope: Envelope::new(),
+        }
+    }
+
+    fn period(&self) -> i32 {
+        (NOISE_DIVISORS[self.divisor_code as usize] << self.clock_shift) as i32
+    }
+
+    fn step(&mut self, cycles: u32) {
+        self.timer -= cycles as i32;
+        while self.timer <= 0 {
+            self.timer += self.period();
+
+            // shift clock values of 14 and 15 stop the LFSR
+            if self.clock_shift >= 14 {
+                continue;
+            }
+
+            let bit = (self.lfsr ^ (self.lfsr >> 1)) & 1;
+            self.lfsr = (self.lfsr >> 1) | (bit << 14);
+            if self.short_mode {
+                self.lfsr = (self.lfsr & !0x40) | (bit << 6);
+            }
+        }
+    }
+
+    fn output(&self) -> u8 {
+        if !self.enabled {
+            return 0;
+        }
+        ((!self.lfsr & 1) as u8) * self.envelope.volume
+    }
+
+    fn trigger(&mut self) {
+        self.enabled = self.dac_enabled;
+        self.timer = self.period();
+        self.lfsr = 0x7FFF;
+        self.envelope.trigger();
+    }
+}
+
+pub struct APU {
+    pub powered: bool,
+    registers: [u8; 0x30], // 0xFF10 - 0xFF3F as last written, wave RAM included
+    channel1: SquareChannel,
+    channel2: SquareChannel,
+    channel3: WaveChannel,
+    channel4: NoiseChannel,
+    frame_step: u8,
+    frame_clock: u32,
+    sample_clock: u32,
+    sample_left: f32,
+    sample_right: f32,
+    sample_count: u32,
+    capacitor_left: f32,
+    capacitor_right: f32,
+    channel_sums: [f32; 4],
+    channel_capacitors: [f32; 4],
+    // interleaved stereo samples (left, right) at SAMPLE_RATE
+    pub samples: Vec<f32>,
+    // receives every sample as it is produced, independent of the frontend draining `samples`
+    pub recorder: Option<AudioRecorder>,
+    // why the recorder was dropped, until the bus reports it
+    pub recording_error: Option<io::Error>,
+    pub model: Model,
+}
+
+impl APU {
+    pub fn new() -> APU {
+        APU {
+            powered: false,
+            registers: [0; 0x30],
+            channel1: SquareChannel::new(true),
+            channel2: SquareChannel::new(false),
+            channel3: WaveChannel::new(),
+            channel4: NoiseChannel::new(),
+            frame_step: 0,
+            frame_clock: 0,
+            sample_clock: 0,
+            sample_left: 0.0,
+            sample_right: 0.0,
+            sample_count: 0,
+            capacitor_left: 0.0,
+            capacitor_right: 0.0,
+            channel_sums: [0.0; 4],
+            channel_capacitors: [0.0; 4],
+            samples: Vec::new(),
+            recorder: None,
+            recording_error: None,
+            model: Model::default(),
+        }
+    }
+
+    // advances the APU by a number of T-cycles (always a multiple of 4)
+    pub fn step(&mut self, cycles: u32) {
+        for _ in 0..cycles / 4 {
+            self.tick();
+        }
+    }
+
+    fn tick(&mut self) {
+        if self.powered {
+            self.frame_clock += 4;
+            if self.frame_clock >= CYCLES_PER_FRAME_STEP {
+                self.frame_clock -= CYCLES_PER_FRAME_STEP;
+                self.clock_frame_sequencer();
+            }
+
+            self.channel1.step(4);
+            self.channel2.step(4);
+            self.channel3.step(4, &self.registers[0x20..0x30]);
+            self.channel4.step(4);
+        }
+
+        let (left, right) = self.mix();
+        self.sample_left += left;
+        self.sample_right += right;
+        self.sample_count += 1;
+
+        let record_channels =
+            matches!(&self.recorder, Some(recorder) if recorder.records_channels());
+        if record_channels {
+            let outputs = self.channel_outputs();
+            for (sum, output) in self.channel_sums.iter_mut().zip(outputs) {
+                *sum += output;
+            }
+        }
+
+        // average every output over the sample period as a cheap low-pass filter
+        self.sample_clock += 4;
+        if self.sample_clock >= CYCLES_PER_SAMPLE {
+            self.sample_clock -= CYCLES_PER_SAMPLE;
+            let count = self.sample_count as f32;
+            let left = APU::high_pass(&mut self.capacitor_left, self.sample_left / count);
+            let right = APU::high_pass(&mut self.capacitor_right, self.sample_right / count);
+            if self.samples.len() < MAX_BUFFERED_SAMPLES {
+                self.samples.push(left);
+                self.samples.push(right);
+            }
+
+            let mut channels = [0.0; 4];
+            if record_channels {
+                for (i, channel) in channels.iter_mut().enumerate() {
+                    // a quarter of full scale, the share each channel gets in the mix
+                    let average = self.channel_sums[i] / count / 4.0;
+                    *channel = APU::high_pass(&mut self.channel_capacitors[i], average);
+                }
+            }
+            self.record(left, right, channels);
+
+            self.sample_left = 0.0;
+            self.sample_right = 0.0;
+            self.sample_count = 0;
+            self.channel_sums = [0.0; 4];
+        }
+    }
+
+    fn high_pass(capacitor: &mut f32, input: f32) -> f32 {
+        let output = input - *capacitor;
+        *capacitor = input - output * HIGH_PASS_CHARGE;
+        output
+    }
+
+    fn record(&mut self, left: f32, right: f32, channels: [f32; 4]) {
+        let Some(recorder) = self.recorder.as_mut() else {
+            return;
+        };
+        if let Err(err) = recorder.write(left, right, channels) {
+            self.recording_error = Some(err);
+            self.recorder = None;
+        }
+    }
+
+    fn clock_frame_sequencer(&mut self) {
+        if self.frame_step.is_multiple_of(2) {
+            self.clock_lengths();
+        }
+        if self.frame_step == 2 || self.frame_step == 6 {
+            self.channel1.clock_sweep();
+        }
+        if self.frame_step == 7 {
+            self.channel1.envelope.clock();
+            self.channel2.envelope.clock();
+            self.channel4.envelope.clock();
+        }
+
+        self.frame_step = (self.frame_step + 1) & 0x07;
+    }
+
+    fn clock_lengths(&mut self) {
+        if self.channel1.length.clock() {
+            self.channel1.enabled = false;
+        }
+        if self.channel2.length.clock() {
+            self.channel2.enabled = false;
+        }
+        if self.channel3.length.clock() {
+            self.channel3.enabled = false;
+        }
+        if self.channel4.length.clock() {
+            self.channel4.enabled = false;
+        }
+    }
+
+    // true while the next frame sequencer step won't clock the length counters
+    fn length_first_half(&self) -> bool {
+        !self.frame_step.is_multiple_of(2)
+    }
+
+    // converts a 4-bit channel output to [-1, 1], silent when the channel's DAC is off
+    fn dac(dac_enabled: bool, value: u8) -> f32 {
+        if dac_enabled {
+            1.0 - value as f32 / 7.5
+        } else {
+            0.0
+        }
+    }
+
+    // per-channel analog outputs in channel order
+    pub fn channel_outputs(&self) -> [f32; 4] {
+        [
+            APU::dac(self.channel1.dac_enabled, self.channel1.output()),
+            APU::dac(self.channel2.dac_enabled, self.channel2.output()),
+            APU::dac(self.channel3.dac_enabled, self.channel3.output()),
+            APU::dac(self.channel4.dac_enabled, self.channel4.output()),
+        ]
+    }
+
+    fn mix(&self) -> (f32, f32) {
+        if !self.powered {
+            return (0.0, 0.0);
+        }
+
+        let nr50 = self.registers[0x14];
+        let nr51 = self.registers[0x15];
+
+        let mut left = 0.0;
+        let mut right = 0.0;
+        for (i, output) in self.channel_outputs().iter().enumerate() {
+            if nr51 & (0x10 << i) != 0 {
+                left += output;
+            }
+            if nr51 & (0x01 << i) != 0 {
+                right += output;
+            }
+        }
+
+        let left_volume = ((nr50 >> 4) & 0x07) as f32 + 1.0;
+        let right_volume = (nr50 & 0x07) as f32 + 1.0;
+
+        (left * left_volume / 32.0, right * right_volume / 32.0)
+    }
+
+    pub fn take_samples(&mut self) -> Vec<f32> {
+        std::mem::take(&mut self.samples)
+    }
+
+    pub fn read_byte(&self, addr: u16) -> u8 {
+        let index = (addr - 0xFF10) as usize;
+        match addr {
+            0xFF26 => {
+                0x70 | ((self.powered as u8) << 7)
+                    | (self.channel1.enabled as u8)
+                    | (self.channel2.enabled as u8) << 1
+                    | (self.channel3.enabled as u8) << 2
+                    | (self.channel4.enabled as u8) << 3
+            }
+            0xFF10..=0xFF2F => self.registers[index] | READ_MASKS[index],
+            0xFF30..=0xFF3F => {
+                // while the wave channel plays, the CGB sees the byte it is reading; the DMG
+                // only does on the exact cycle of the channel's own read, which isn't modeled
+                if !self.channel3.enabled {
+                    self.registers[index]
+                } else if self.model.has_dmg_apu() {
+                    0xFF
+                } else {
+                    self.registers[0x20 + (self.channel3.position / 2) as usize]
+                }
+            }
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_byte(&mut self, addr: u16, value: u8) {
+        let index = (addr - 0xFF10) as usize;
+
+        if let 0xFF30..=0xFF3F = addr {
+            if !self.channel3.enabled {
+                self.registers[index] = value;
+            } else if !self.model.has_dmg_apu() {
+                self.registers[0x20 + (self.channel3.position / 2) as usize] = value;
+            }
+            return;
+        }
+
+        if addr == 0xFF26 {
+            self.write_power(value);
+            return;
+        }
+
+        // while powered off only NR52 and wave RAM are writable, plus the length
+        // counters on DMG-family hardware
+        if !self.powered {
+            if self.model.has_dmg_apu() {
+                self.write_length_powered_off(addr, value);
+            }
+            return;
+        }
+
+        self.registers[index] = value;
+        let first_half = self.length_first_half();
+
+        match addr {
+            // channel 1
+            0xFF10 => {
+                if let Some(sweep) = self.channel1.sweep.as_mut() {
+                    if !sweep.write(value) {
+                        self.channel1.enabled = false;
+                    }
+                }
+            }
+            0xFF11 => {
+                self.channel1.duty = value >> 6;
+                self.channel1.length.load((value & 0x3F) as u16);
+            }
+            0xFF12 => {
+                self.channel1.envelope.write(value);
+                self.channel1.dac_enabled = value & 0xF8 != 0;
+                if !self.channel1.dac_enabled {
+                    self.channel1.enabled = false;
+                }
+            }
+            0xFF13 => self.channel1.frequency = (self.channel1.frequency & 0x700) | value as u16,
+            0xFF14 => {
+                let channel = &mut self.channel1;
+                channel.frequency = (channel.frequency & 0xFF) | (((value & 0x07) as u16) << 8);
+                if channel.length.write_control(value, first_half) {
+                    channel.enabled = false;
+                }
+                if value & 0x80 != 0 {
+                    channel.trigger();
+                }
+            }
+
+            // channel 2
+            0xFF16 => {
+                self.channel2.duty = value >> 6;
+                self.channel2.length.load((value & 0x3F) as u16);
+            }
+            0xFF17 => {
+                self.channel2.envelope.write(value);
+                self.channel2.dac_enabled = value & 0xF8 != 0;
+                if !self.channel2.dac_enabled {
+                    self.channel2.enabled = false;
+                }
+            }
+            0xFF18 => self.channel2.frequency = (self.channel2.frequency & 0x700) | value as u16,
+            0xFF19 => {
+                let channel = &mut self.channel2;
+                channel.frequency = (channel.frequency & 0xFF) | (((value & 0x07) as u16) << 8);
+                if channel.length.write_control(value, first_half) {
+                    channel.enabled = false;
+                }
+                if value & 0x80 != 0 {
+                    channel.trigger();
+                }
+            }
+
+            // channel 3
+            0xFF1A => {
+                self.channel3.dac_enabled = value & 0x80 != 0;
+                if !self.channel3.dac_enabled {
+                    self.channel3.enabled = false;
+                }
+            }
+            0xFF1B => self.channel3.length.load(value as u16),
+            0xFF1C => self.channel3.volume_code = (value >> 5) & 0x03,
+            0xFF1D => self.channel3.frequency = (self.channel3.frequency & 0x700) | value as u16,
+            0xFF1E => {
+                let channel = &mut self.channel3;
+                channel.frequency = (channel.frequency & 0xFF) | (((value & 0x07) as u16) << 8);
+                if channel.length.write_control(value, first_half) {
+                    channel.enabled = false;
+                }
+                if value & 0x80 != 0 {
+                    channel.trigger();
+                }
+            }
+
+            // channel 4
+            0xFF20 => self.channel4.length.load((value & 0x3F) as u16),
+            0xFF21 => {
+                self.channel4.envelope.write(value);
+                self.channel4.dac_enabled = value & 0xF8 != 0;
+                if !self.channel4.dac_enabled {
+                    self.channel4.enabled = false;
+                }
+            }
+            0xFF22 => {
+                self.channel4.clock_shift = value >> 4;
+                self.channel4.short_mode = value & 0x08 != 0;
+                self.channel4.divisor_code = value & 0x07;
+            }
+            0xFF23 => {
+                let channel = &mut self.channel4;
+                if channel.length.write_control(value, first_half) {
+                    channel.enabled = false;
+                }
+                if value & 0x80 != 0 {
+                    channel.trigger();
+                }
+            }
+
+            _ => (),
+        }
+    }
+
+    fn write_length_powered_off(&mut self, addr: u16, value: u8) {
+        match addr {
+            0xFF11 => self.channel1.length.load((value & 0x3F) as u16),
+            0xFF16 => self.channel2.length.load((value & 0x3F) as u16),
+            0xFF1B => self.channel3.length.load(value as u16),
+            0xFF20 => self.channel4.length.load((value & 0x3F) as u16),
+            _ => (),
+        }
+    }
+
+    fn write_power(&mut self, value: u8) {
+        let power = value & 0x80 != 0;
+
+        if self.powered && !power {
+            let counters = [
+                self.channel1.length.counter,
+                self.channel2.length.counter,
+                self.channel3.length.counter,
+                self.channel4.length.counter,
+            ];
+
+            // powering off clears every register except wave RAM
+            for addr in 0xFF10..0xFF26 {
+                self.write_byte(addr, 0);
+            }
+            self.channel1 = SquareChannel::new(true);
+            self.channel2 = SquareChannel::new(false);
+            self.channel3.enabled = false;
+            self.channel3.dac_enabled = false;
+            self.channel3.length = LengthCounter::new(256);
+            self.channel4 = NoiseChannel::new();
+
+            // DMG-family length counters are not on the power-controlled reset line
+            if self.model.has_dmg_apu() {
+                self.channel1.length.counter = counters[0];
+                self.channel2.length.counter = counters[1];
+                self.channel3.length.counter = counters[2];
+                self.channel4.length.counter = counters[3];
+            }
+        } else if !self.powered && power {
+            self.frame_step = 0;
+            self.frame_clock = 0;
+            self.channel1.duty_step = 0;
+            self.channel2.duty_step = 0;
+            self.channel3.sample = 0;
+        }
+
+        self.powered = power;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{APU, CYCLES_PER_FRAME_STEP, CYCLES_PER_SAMPLE};
+    use crate::model::Model;
+
+    fn powered_apu() -> APU {
+        let mut apu = APU::new();
+        apu.write_byte(0xFF26, 0x80);
+        apu
+    }
+
+    fn channel_status(apu: &APU) -> u8 {
+        apu.read_byte(0xFF26) & 0x0F
+    }
+
+    #[test]
+    fn registers_read_back_with_unused_bits_set() {
+        let mut apu = powered_apu();
+        apu.write_byte(0xFF10, 0x00);
+        assert_eq!(apu.read_byte(0xFF10), 0x80);
+        apu.write_byte(0xFF13, 0x12);
+        assert_eq!(apu.read_byte(0xFF13), 0xFF);
+        apu.write_byte(0xFF24, 0x77);
+        assert_eq!(apu.read_byte(0xFF24), 0x77);
+        assert_eq!(apu.read_byte(0xFF26), 0xF0);
+    }
+
+    #[test]
+    fn trigger_needs_the_dac() {
+        let mut apu = powered_apu();
+        apu.write_byte(0xFF14, 0x80);
+        assert_eq!(channel_status(&apu), 0);
+
+        apu.write_byte(0xFF12, 0xF0);
+        apu.write_byte(0xFF14, 0x80);
+        assert_eq!(channel_status(&apu), 0x01);
+
+        // turning the DAC off stops the channel
+        apu.write_byte(0xFF12, 0x00);
+        assert_eq!(channel_status(&apu), 0);
+    }
+
+    #[test]
+    fn length_counter_expires() {
+        let mut apu = powered_apu();
+        apu.write_byte(0xFF17, 0xF0);
+        apu.write_byte(0xFF16, 0x3E);
+        apu.write_byte(0xFF19, 0xC0);
+        assert_eq!(channel_status(&apu), 0x02);
+
+        // lengths are clocked on every other frame sequencer step
+        apu.step(CYCLES_PER_FRAME_STEP);
+        assert_eq!(channel_status(&apu), 0x02);
+        apu.step(CYCLES_PER_FRAME_STEP * 2);
+        assert_eq!(channel_status(&apu), 0);
+    }
+
+    #[test]
+    fn sweep_overflow_on_trigger_disables_channel_1() {
+        let mut apu = powered_apu();
+        apu.write_byte(0xFF12, 0xF0);
+        apu.write_byte(0xFF10, 0x11);
+        apu.write_byte(0xFF13, 0xFF);
+        apu.write_byte(0xFF14, 0x87);
+        assert_eq!(channel_status(&apu), 0);
+    }
+
+    #[test]
+    fn power_off_clears_registers_but_not_wave_ram() {
+        let mut apu = powered_apu();
+        apu.write_byte(0xFF24, 0x77);
+        apu.write_byte(0xFF30, 0xAB);
+        apu.write_byte(0xFF26, 0x00);
+        assert_eq!(apu.read_byte(0xFF24), 0x00);
+        assert_eq!(apu.read_byte(0xFF30), 0xAB);
+
+        // and ignores register writes until powered on again
+        apu.write_byte(0xFF24, 0x77);
+        assert_eq!(apu.read_byte(0xFF24), 0x00);
+        apu.write_byte(0xFF31, 0xCD);
+        assert_eq!(apu.read_byte(0xFF31), 0xCD);
+    }
+
+    #[test]
+    fn dmg_length_counters_are_writable_while_off() {
+        for (model, expired) in [(Model::Dmg, true), (Model::Cgb, false)] {
+            let mut apu = APU::new();
+            apu.model = model;
+            // a length of 1 loaded while off
+            apu.write_byte(0xFF16, 0x3F);
+            apu.write_byte(0xFF26, 0x80);
+            apu.write_byte(0xFF17, 0xF0);
+            apu.write_byte(0xFF19, 0xC0);
+            apu.step(CYCLES_PER_FRAME_STEP);
+            assert_eq!(channel_status(&apu) == 0, expired, "{:?}", model);
+        }
+    }
+
+    #[test]
+    fn one_stereo_sample_per_period() {
+        let mut apu = powered_apu();
+        apu.step(CYCLES_PER_SAMPLE * 10);
+        assert_eq!(apu.take_samples().len(), 20);
+        assert!(apu.take_samples().is_empty());
+    }
+}
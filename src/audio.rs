@@ -0,0 +1,570 @@
+use std::{
+    f64::consts::PI,
+    fs::File,
+    io::{self, BufWriter, Write},
+    path::Path,
+    process::{Child, Command, Stdio},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+// host output rate used by the bundled sinks
+pub const HOST_SAMPLE_RATE: u32 = 48_000;
+
+// sinks buffer up to this much audio; pacing aims to keep half of it queued
+const SINK_BUFFER_MS: usize = 100;
+
+// maximum resampling ratio adjustment used to keep the sink's queue at its target
+const MAX_RATE_DELTA: f64 = 0.005;
+
+const RESAMPLER_TAPS: usize = 32;
+const RESAMPLER_PHASES: usize = 256;
+
+// fixed-capacity FIFO of interleaved samples
+pub struct RingBuffer {
+    data: Vec<f32>,
+    read: usize,
+    len: usize,
+}
+
+impl RingBuffer {
+    pub fn new(capacity: usize) -> RingBuffer {
+        RingBuffer {
+            data: vec![0.0; capacity],
+            read: 0,
+            len: 0,
+        }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len == 0
+    }
+
+    pub fn capacity(&self) -> usize {
+        self.data.len()
+    }
+
+    // appends as many samples as fit, returns how many were written
+    pub fn push(&mut self, samples: &[f32]) -> usize {
+        let count = samples.len().min(self.capacity() - self.len);
+        for &sample in &samples[..count] {
+            let write = (self.read + self.len) % self.capacity();
+            self.data[write] = sample;
+            self.len += 1;
+        }
+        count
+    }
+
+    // removes up to out.len() samples into out, returns how many were read
+    pub fn pop(&mut self, out: &mut [f32]) -> usize {
+        let count = out.len().min(self.len);
+        for sample in &mut out[..count] {
+            *sample = self.data[self.read];
+            self.read = (self.read + 1) % self.capacity();
+            self.len -= 1;
+        }
+        count
+    }
+
+    // drops up to count samples from the front
+    pub fn discard(&mut self, count: usize) {
+        let count = count.min(self.len);
+        self.read = (self.read + count) % self.capacity();
+        self.len -= count;
+    }
+}
+
+pub trait AudioSink {
+    fn sample_rate(&self) -> u32;
+    // queues interleaved stereo samples at sample_rate() for playback
+    fn write(&mut self, samples: &[f32]);
+    // stereo frames still waiting to be played, used for pacing and rate control
+    fn queued_frames(&mut self) -> usize;
+    // how many frames the sink can hold before dropping audio
+    fn capacity_frames(&self) -> usize;
+}
+
+fn buffer_frames(sample_rate: u32) -> usize {
+    sample_rate as usize * SINK_BUFFER_MS / 1000
+}
+
+// discards audio but consumes it in real time, so it still paces emulation like a device would
+pub struct NullSink {
+    ring: RingBuffer,
+    sample_rate: u32,
+    last_drain: Instant,
+}
+
+impl NullSink {
+    pub fn new(sample_rate: u32) -> NullSink {
+        NullSink {
+            ring: RingBuffer::new(buffer_frames(sample_rate) * 2),
+            sample_rate,
+            last_drain: Instant::now(),
+        }
+    }
+
+    fn drain(&mut self) {
+        let elapsed = self.last_drain.elapsed();
+        let frames = (elapsed.as_secs_f64() * self.sample_rate as f64) as usize;
+        if frames > 0 {
+            self.ring.discard(frames * 2);
+            self.last_drain += Duration::from_secs_f64(frames as f64 / self.sample_rate as f64);
+        }
+    }
+}
+
+impl AudioSink for NullSink {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        self.drain();
+        self.ring.push(samples);
+    }
+
+    fn queued_frames(&mut self) -> usize {
+        self.drain();
+        self.ring.len() / 2
+    }
+
+    fn capacity_frames(&self) -> usize {
+        self.ring.capacity() / 2
+    }
+}
+
+// writes raw interleaved little-endian f32 PCM as fast as it is produced
+pub struct FileSink {
+    writer: BufWriter<File>,
+    sample_rate: u32,
+    // the first failed write; nothing more is written after it
+    error: Option<io::Error>,
+}
+
+impl FileSink {
+    pub fn create(path: &Path, sample_rate: u32) -> io::Result<FileSink> {
+        Ok(FileSink {
+            writer: BufWriter::new(File::create(path)?),
+            sample_rate,
+            error: None,
+        })
+    }
+
+    // why the sink stopped writing, if it did
+    pub fn take_error(&mut self) -> Option<io::Error> {
+        self.error.take()
+    }
+}
+
+impl AudioSink for FileSink {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        if self.error.is_some() {
+            return;
+        }
+        for sample in samples {
+            if let Err(e) = self.writer.write_all(&sample.to_le_bytes()) {
+                self.error = Some(e);
+                return;
+            }
+        }
+    }
+
+    fn queued_frames(&mut self) -> usize {
+        0
+    }
+
+    fn capacity_frames(&self) -> usize {
+        buffer_frames(self.sample_rate)
+    }
+}
+
+impl Drop for FileSink {
+    fn drop(&mut self) {
+        let _ = self.writer.flush();
+    }
+}
+
+// streams raw f32 PCM into the stdin of an external player such as aplay or pacat;
+// a feeder thread drains the ring buffer at whatever pace the player accepts
+pub struct CommandSink {
+    ring: Arc<Mutex<RingBuffer>>,
+    running: Arc<AtomicBool>,
+    feeder: Option<JoinHandle<()>>,
+    child: Child,
+    sample_rate: u32,
+}
+
+impl CommandSink {
+    pub fn spawn(program: &str, args: &[&str], sample_rate: u32) -> io::Result<CommandSink> {
+        let mut child = Command::new(program)
+            .args(args)
+            .stdin(Stdio::piped())
+            .stdout(Stdio::null())
+            .spawn()?;
+        let mut stdin = child.stdin.take().expect("child stdin is piped");
+
+        let ring = Arc::new(Mutex::new(RingBuffer::new(buffer_frames(sample_rate) * 2)));
+        let running = Arc::new(AtomicBool::new(true));
+
+        let feeder = {
+            let ring = ring.clone();
+            let running = running.clone();
+            thread::spawn(move || {
+                let mut chunk = vec![0.0f32; 512];
+                let mut bytes = Vec::with_capacity(chunk.len() * 4);
+                while running.load(Ordering::Relaxed) {
+                    let count = ring.lock().unwrap().pop(&mut chunk);
+                    if count == 0 {
+                        thread::sleep(Duration::from_millis(1));
+                        continue;
+                    }
+
+                    bytes.clear();
+                    for sample in &chunk[..count] {
+                        bytes.extend_from_slice(&sample.to_le_bytes());
+                    }
+                    if stdin.write_all(&bytes).is_err() {
+                        break;
+                    }
+                }
+            })
+        };
+
+        Ok(CommandSink {
+            ring,
+            running,
+            feeder: Some(feeder),
+            child,
+            sample_rate,
+        })
+    }
+
+    // plays through ALSA's aplay, available on most Linux desktops
+    pub fn aplay(sample_rate: u32) -> io::Result<CommandSink> {
+        let rate = sample_rate.to_string();
+        CommandSink::spawn(
+            "aplay",
+            &["-q", "-t", "raw", "-f", "FLOAT_LE", "-c", "2", "-r", &rate],
+            sample_rate,
+        )
+    }
+}
+
+impl AudioSink for CommandSink {
+    fn sample_rate(&self) -> u32 {
+        self.sample_rate
+    }
+
+    fn write(&mut self, samples: &[f32]) {
+        self.ring.lock().unwrap().push(samples);
+    }
+
+    fn queued_frames(&mut self) -> usize {
+        self.ring.lock().unwrap().len() / 2
+    }
+
+    fn capacity_frames(&self) -> usize {
+        self.ring.lock().unwrap().capacity() / 2
+    }
+}
+
+impl Drop for CommandSink {
+    fn drop(&mut self) {
+        self.running.store(false, Ordering::Relaxed);
+        // killing the player first unblocks a feeder stuck writing to a full pipe
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+        if let Some(feeder) = self.feeder.take() {
+            let _ = feeder.join();
+        }
+    }
+}
+
+// band-limited stereo resampler using a polyphase windowed-sinc filter
+pub struct Resampler {
+    input_rate: f64,
+    output_rate: f64,
+    table: Vec<[f32; RESAMPLER_TAPS]>,
+    history: Vec<[f32; 2]>,
+    // position of the next output frame in history, in input frames
+    position: f64,
+}
+
+impl Resampler {
+    pub fn new(input_rate: u32, output_rate: u32) -> Resampler {
+        let input_rate = input_rate as f64;
+        let output_rate = output_rate as f64;
+
+        // cut off a little below the lower of the two Nyquist frequencies
+        let cutoff = 0.45 * (output_rate / input_rate).min(1.0);
+
+        let half = (RESAMPLER_TAPS / 2) as f64;
+        let mut table = Vec::with_capacity(RESAMPLER_PHASES + 1);
+        for phase in 0..=RESAMPLER_PHASES {
+            let fraction = phase as f64 / RESAMPLER_PHASES as f64;
+            let mut taps = [0.0f64; RESAMPLER_TAPS];
+            for (k, tap) in taps.iter_mut().enumerate() {
+                let x = k as f64 - (half - 1.0) - fraction;
+                let sinc = if x == 0.0 {
+                    1.0
+                } else {
+                    (2.0 * PI * cutoff * x).sin() / (2.0 * PI * cutoff * x)
+                };
+                // Blackman window over the span of the taps
+                let w = (x + half) / RESAMPLER_TAPS as f64;
+                let window = 0.42 - 0.5 * (2.0 * PI * w).cos() + 0.08 * (4.0 * PI * w).cos();
+                *tap = sinc * window;
+            }
+
+            // normalise every phase to unity gain so DC passes unchanged
+            let sum: f64 = taps.iter().sum();
+            let mut row = [0.0f32; RESAMPLER_TAPS];
+            for (out, tap) in row.iter_mut().zip(taps.iter()) {
+                *out = (tap / sum) as f32;
+            }
+            table.push(row);
+        }
+
+        Resampler {
+            input_rate,
+            output_rate,
+            table,
+            history: vec![[0.0; 2]; RESAMPLER_TAPS],
+            position: (RESAMPLER_TAPS / 2 - 1) as f64,
+        }
+    }
+
+    // resamples interleaved stereo input, stretching the output rate by `rate_adjust`
+    pub fn process(&mut self, input: &[f32], rate_adjust: f64, output: &mut Vec<f32>) {
+        self.history
+            .extend(input.chunks_exact(2).map(|frame| [frame[0], frame[1]]));
+
+        let step = self.input_rate / (self.output_rate * rate_adjust);
+        let Some(last_start) = self.history.len().checked_sub(RESAMPLER_TAPS) else {
+            return;
+        };
+
+        loop {
+            let index = self.position as usize;
+            let start = index + 1 - RESAMPLER_TAPS / 2;
+            if start > last_start {
+                break;
+            }
+
+            let fraction = self.position - index as f64;
+            let taps = &self.table[(fraction * RESAMPLER_PHASES as f64).round() as usize];
+
+            let mut left = 0.0;
+            let mut right = 0.0;
+            for (frame, tap) in self.history[start..start + RESAMPLER_TAPS].iter().zip(taps) {
+                left += frame[0] * tap;
+                right += frame[1] * tap;
+            }
+            output.push(left);
+            output.push(right);
+
+            self.position += step;
+        }
+
+        // keep only the frames the next outputs still need
+        let consumed = (self.position as usize + 1).saturating_sub(RESAMPLER_TAPS / 2);
+        self.history.drain(..consumed);
+        self.position -= consumed as f64;
+    }
+}
+
+// resamples emulator audio to the sink's rate and nudges the ratio so the sink's
+// queue hovers around half full, which keeps latency bounded without underruns
+pub struct AudioOutput {
+    sink: Box<dyn AudioSink>,
+    resampler: Resampler,
+    buffer: Vec<f32>,
+}
+
+impl AudioOutput {
+    pub fn new(sink: Box<dyn AudioSink>, input_rate: u32) -> AudioOutput {
+        let resampler = Resampler::new(input_rate, sink.sample_rate());
+        AudioOutput {
+            sink,
+            resampler,
+            buffer: Vec::new(),
+        }
+    }
+
+    fn target_frames(&self) -> usize {
+        self.sink.capacity_frames() / 2
+    }
+
+    pub fn push(&mut self, samples: &[f32]) {
+        let target = self.target_frames() as f64;
+        let fill = (self.sink.queued_frames() as f64 / (2.0 * target)).min(1.0);
+        // below target produce slightly more output per input, above target slightly less
+        let rate_adjust = 1.0 + MAX_RATE_DELTA * (1.0 - 2.0 * fill);
+
+        self.buffer.clear();
+        self.resampler
+            .process(samples, rate_adjust, &mut self.buffer);
+        self.sink.write(&self.buffer);
+    }
+
+    // true while the sink wants more audio, i.e. the emulator should run another frame
+    pub fn needs_samples(&mut self) -> bool {
+        self.sink.queued_frames() < self.target_frames()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::fs;
+    use std::rc::Rc;
+    use std::time::{Duration, Instant};
+
+    use super::{AudioOutput, AudioSink, FileSink, NullSink, Resampler, RingBuffer};
+
+    #[test]
+    fn ring_buffer_wraps_and_drops_overflow() {
+        let mut ring = RingBuffer::new(4);
+        assert_eq!(ring.push(&[1.0, 2.0, 3.0]), 3);
+        let mut out = [0.0; 2];
+        assert_eq!(ring.pop(&mut out), 2);
+        assert_eq!(out, [1.0, 2.0]);
+
+        // wraps around the end of the storage, the fifth sample doesn't fit
+        assert_eq!(ring.push(&[4.0, 5.0, 6.0, 7.0]), 3);
+        assert_eq!(ring.len(), 4);
+        ring.discard(1);
+        let mut out = [0.0; 4];
+        assert_eq!(ring.pop(&mut out), 3);
+        assert_eq!(out[..3], [4.0, 5.0, 6.0]);
+        assert!(ring.is_empty());
+    }
+
+    #[test]
+    fn resampler_passes_dc() {
+        let mut resampler = Resampler::new(65536, 48000);
+        let mut output = Vec::new();
+        resampler.process(&[0.5; 2 * 4096], 1.0, &mut output);
+        // skip the filter warming up from silence
+        for sample in &output[64..] {
+            assert!((sample - 0.5).abs() < 1e-3, "{}", sample);
+        }
+    }
+
+    #[test]
+    fn resampler_output_follows_the_rate_ratio() {
+        for (input_rate, output_rate) in [(65536, 48000), (32768, 48000), (48000, 48000)] {
+            let mut resampler = Resampler::new(input_rate, output_rate);
+            let mut output = Vec::new();
+            // one second in chunks, as the frame loop feeds it
+            for _ in 0..64 {
+                resampler.process(&vec![0.0; input_rate as usize / 32], 1.0, &mut output);
+            }
+            let frames = output.len() as i64 / 2;
+            assert!(
+                (frames - output_rate as i64).abs() <= 32,
+                "{} -> {}: {} frames",
+                input_rate,
+                output_rate,
+                frames
+            );
+        }
+    }
+
+    #[test]
+    fn null_sink_drains_in_real_time() {
+        let mut sink = NullSink::new(48000);
+        sink.write(&[0.0; 2 * 2400]);
+        assert!(sink.queued_frames() > 2000);
+
+        // pretend 50 ms have passed since the last drain
+        sink.last_drain = Instant::now() - Duration::from_millis(50);
+        assert!(sink.queued_frames() < 100);
+    }
+
+    #[test]
+    fn file_sink_writes_raw_f32() {
+        let path = std::env::temp_dir().join(format!("lr35902-{}-sink.raw", std::process::id()));
+        let mut sink = FileSink::create(&path, 48000).unwrap();
+        sink.write(&[0.25, -1.0]);
+        assert!(sink.take_error().is_none());
+        drop(sink);
+
+        let data = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        let mut expected = 0.25f32.to_le_bytes().to_vec();
+        expected.extend_from_slice(&(-1.0f32).to_le_bytes());
+        assert_eq!(data, expected);
+    }
+
+    // a sink with a fixed queue level that counts what it's given
+    struct FixedSink {
+        queued: usize,
+        written: Rc<RefCell<usize>>,
+    }
+
+    impl AudioSink for FixedSink {
+        fn sample_rate(&self) -> u32 {
+            48000
+        }
+
+        fn write(&mut self, samples: &[f32]) {
+            *self.written.borrow_mut() += samples.len() / 2;
+        }
+
+        fn queued_frames(&mut self) -> usize {
+            self.queued
+        }
+
+        fn capacity_frames(&self) -> usize {
+            4800
+        }
+    }
+
+    fn frames_written(queued: usize) -> usize {
+        let written = Rc::new(RefCell::new(0));
+        let sink = FixedSink {
+            queued,
+            written: written.clone(),
+        };
+        let mut output = AudioOutput::new(Box::new(sink), 48000);
+        for _ in 0..100 {
+            output.push(&[0.0; 2 * 4800]);
+        }
+        let frames = *written.borrow();
+        frames
+    }
+
+    #[test]
+    fn output_rate_follows_the_sink_queue() {
+        let starved = frames_written(0);
+        let balanced = frames_written(2400);
+        let full = frames_written(4800);
+        assert!(starved > balanced && balanced > full);
+        // the nudge stays within MAX_RATE_DELTA of the nominal rate
+        assert!(starved <= 480_000 * 1006 / 1000);
+        assert!(full >= 480_000 * 994 / 1000);
+
+        let mut output = AudioOutput::new(
+            Box::new(FixedSink {
+                queued: 2399,
+                written: Rc::new(RefCell::new(0)),
+            }),
+            48000,
+        );
+        assert!(output.needs_samples());
+    }
+}
@@ -0,0 +1,160 @@
+use std::cell::RefCell;
+use std::rc::Rc;
+
+use crate::{
+    device::{DeviceEvent, SharedMemory},
+    memory::Memory,
+    ppu::PPU,
+};
+
+// everything the CPU is wired to: the address space plus the hardware that runs off
+// the same clock. the CPU only talks to the rest of the system through this
+pub trait Bus {
+    // a CPU memory access; no time passes
+    fn read(&self, addr: u16) -> u8;
+    fn write(&mut self, addr: u16, value: u8);
+
+    // what's at addr without a CPU access: ignores OAM DMA and leaves nothing behind
+    fn peek(&self, addr: u16) -> u8 {
+        self.read(addr)
+    }
+
+    // runs the rest of the system for `cycles` CPU T-cycles and returns how many
+    // passed on the system clock, which is fewer in CGB double speed
+    fn tick(&mut self, cycles: u32) -> u32 {
+        cycles
+    }
+
+    // a pressed button is pulling a joypad input line low, which ends STOP
+    fn joypad_line_low(&self) -> bool {
+        false
+    }
+
+    // STOP with a CGB speed switch armed in KEY1: switch speed and return true
+    fn switch_speed(&mut self) -> bool {
+        false
+    }
+
+    // STOP without a speed switch, the LCD goes blank
+    fn stop(&mut self) {}
+
+    fn illegal_opcode(&mut self, _opcode: u8, _pc: u16) {}
+
+    // something the CPU recovered from, passed on to whoever runs the system
+    fn warn(&mut self, _message: String) {}
+
+    // ROM bank mapped at addr, 0 outside the cartridge ROM
+    fn rom_bank(&self, _addr: u16) -> u16 {
+        0
+    }
+}
+
+// the Game Boy itself: shared memory, I/O and the PPU
+pub struct SystemBus {
+    pub memory: SharedMemory,
+    pub ppu: Rc<RefCell<PPU>>,
+    // CPU cycles left over from halving in double speed mode
+    speed_carry: u32,
+}
+
+impl SystemBus {
+    pub fn new(memory: SharedMemory, ppu: Rc<RefCell<PPU>>) -> SystemBus {
+        SystemBus {
+            memory,
+            ppu,
+            speed_carry: 0,
+        }
+    }
+
+    // while OAM DMA runs the CPU can only reach HRAM and the internal I/O registers
+    fn blocked_by_dma(memory: &Memory, addr: u16) -> bool {
+        addr < 0xFF00 && memory.dma_active()
+    }
+
+    // in CGB double speed the CPU, timer and OAM DMA run twice as fast as the PPU and APU
+    fn system_cycles(&mut self, cycles: u32) -> u32 {
+        if !self.memory.borrow().double_speed {
+            return cycles;
+        }
+        let total = cycles + self.speed_carry;
+        self.speed_carry = total % 8;
+        total / 8 * 4
+    }
+}
+
+impl Bus for SystemBus {
+    fn read(&self, addr: u16) -> u8 {
+        let memory = self.memory.borrow();
+        if SystemBus::blocked_by_dma(&memory, addr) {
+            return 0xFF;
+        }
+        memory.read_byte(addr)
+    }
+
+    fn peek(&self, addr: u16) -> u8 {
+        self.memory.borrow().read_byte(addr)
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        let mut memory = self.memory.borrow_mut();
+        if SystemBus::blocked_by_dma(&memory, addr) {
+            return;
+        }
+        memory.write_byte(addr, value);
+    }
+
+    fn tick(&mut self, cycles: u32) -> u32 {
+        let system_cycles = self.system_cycles(cycles);
+
+        self.ppu.borrow_mut().step(system_cycles);
+
+        let mut memory = self.memory.borrow_mut();
+        memory.apu.step(system_cycles);
+        if let Some(err) = memory.apu.recording_error.take() {
+            memory.warn(format!("stopped audio recording: {}", err));
+        }
+        memory.cartridge.step(system_cycles);
+        memory.step_timer(cycles);
+        memory.step_dma(cycles);
+
+        system_cycles
+    }
+
+    fn joypad_line_low(&self) -> bool {
+        self.memory.borrow().joypad_line_low()
+    }
+
+    fn switch_speed(&mut self) -> bool {
+        let mut memory = self.memory.borrow_mut();
+        if !memory.model.is_cgb() || memory.io[0x4D] & 0x01 == 0 {
+            return false;
+        }
+        memory.io[0x4D] &= !0x01;
+        memory.double_speed = !memory.double_speed;
+        true
+    }
+
+    fn stop(&mut self) {
+        let mut ppu = self.ppu.borrow_mut();
+        ppu.framebuffer.fill(0xFFFFFFFF);
+        ppu.frame_ready = true;
+    }
+
+    fn illegal_opcode(&mut self, opcode: u8, pc: u16) {
+        self.memory
+            .borrow_mut()
+            .events
+            .push(DeviceEvent::IllegalOpcode { opcode, pc });
+    }
+
+    fn warn(&mut self, message: String) {
+        self.memory.borrow_mut().warn(message);
+    }
+
+    fn rom_bank(&self, addr: u16) -> u16 {
+        match addr {
+            0x0000..=0x7FFF => self.memory.borrow().cartridge.rom_bank(addr) as u16,
+            _ => 0,
+        }
+    }
+}
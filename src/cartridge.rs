@@ -0,0 +1,671 @@
+/*
+Cartridge header layout (0x0100 - 0x014F):
+
+0x0100 - 0x0103: Entry point (usually NOP; JP 0x0150).
+0x0104 - 0x0133: Nintendo logo.
+0x0134 - 0x0143: Title (0x013F - 0x0142 manufacturer code and 0x0143 CGB flag on newer carts).
+0x0144 - 0x0145: New licensee code.
+0x0146: SGB flag.
+0x0147: Cartridge type.
+0x0148: ROM size.
+0x0149: RAM size.
+0x014A: Destination code.
+0x014B: Old licensee code.
+0x014C: Mask ROM version number.
+0x014D: Header checksum.
+0x014E - 0x014F: Global checksum (big-endian).
+*/
+
+use crate::error::{Error, Result};
+use crate::mbc1::Mbc1;
+use crate::mbc2::{Mbc2, MBC2_RAM_SIZE};
+use crate::mbc3::{Mbc3, Rtc, RtcClock, RTC_SAVE_SIZE};
+use crate::mbc5::Mbc5;
+
+pub const HEADER_END: usize = 0x0150;
+
+const TITLE_ADDR: usize = 0x0134;
+const CGB_FLAG_ADDR: usize = 0x0143;
+const NEW_LICENSEE_ADDR: usize = 0x0144;
+const SGB_FLAG_ADDR: usize = 0x0146;
+const CARTRIDGE_TYPE_ADDR: usize = 0x0147;
+const ROM_SIZE_ADDR: usize = 0x0148;
+const RAM_SIZE_ADDR: usize = 0x0149;
+const DESTINATION_ADDR: usize = 0x014A;
+const OLD_LICENSEE_ADDR: usize = 0x014B;
+const VERSION_ADDR: usize = 0x014C;
+const HEADER_CHECKSUM_ADDR: usize = 0x014D;
+const GLOBAL_CHECKSUM_ADDR: usize = 0x014E;
+
+pub const ROM_BANK_SIZE: usize = 0x4000;
+pub const RAM_BANK_SIZE: usize = 0x2000;
+
+const MIN_ROM_SIZE: usize = 2 * ROM_BANK_SIZE;
+const MAX_ROM_SIZE: usize = 512 * ROM_BANK_SIZE;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CgbSupport {
+    None,
+    Compatible, // 0x80: works on DMG, enhanced on CGB
+    Only,       // 0xC0: CGB only
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MapperKind {
+    RomOnly,
+    Mbc1,
+    Mbc2,
+    Mbc3,
+    Mbc5,
+    Mbc6,
+    Mbc7,
+    Mmm01,
+    PocketCamera,
+    BandaiTama5,
+    HuC1,
+    HuC3,
+    Unknown,
+}
+
+impl MapperKind {
+    pub fn is_supported(self) -> bool {
+        matches!(
+            self,
+            MapperKind::RomOnly
+                | MapperKind::Mbc1
+                | MapperKind::Mbc2
+                | MapperKind::Mbc3
+                | MapperKind::Mbc5
+        )
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CartridgeType {
+    pub code: u8,
+    pub mapper: MapperKind,
+    pub has_ram: bool,
+    pub has_battery: bool,
+    pub has_timer: bool,
+    pub has_rumble: bool,
+}
+
+impl CartridgeType {
+    pub fn from_code(code: u8) -> CartridgeType {
+        // (mapper, ram, battery, timer, rumble)
+        let (mapper, has_ram, has_battery, has_timer, has_rumble) = match code {
+            0x00 => (MapperKind::RomOnly, false, false, false, false),
+            0x01 => (MapperKind::Mbc1, false, false, false, false),
+            0x02 => (MapperKind::Mbc1, true, false, false, false),
+            0x03 => (MapperKind::Mbc1, true, true, false, false),
+            0x05 => (MapperKind::Mbc2, false, false, false, false),
+            0x06 => (MapperKind::Mbc2, false, true, false, false),
+            0x08 => (MapperKind::RomOnly, true, false, false, false),
+            0x09 => (MapperKind::RomOnly, true, true, false, false),
+            0x0B => (MapperKind::Mmm01, false, false, false, false),
+            0x0C => (MapperKind::Mmm01, true, false, false, false),
+            0x0D => (MapperKind::Mmm01, true, true, false, false),
+            0x0F => (MapperKind::Mbc3, false, true, true, false),
+            0x10 => (MapperKind::Mbc3, true, true, true, false),
+            0x11 => (MapperKind::Mbc3, false, false, false, false),
+            0x12 => (MapperKind::Mbc3, true, false, false, false),
+            0x13 => (MapperKind::Mbc3, true, true, false, false),
+            0x19 => (MapperKind::Mbc5, false, false, false, false),
+            0x1A => (MapperKind::Mbc5, true, false, false, false),
+            0x1B => (MapperKind::Mbc5, true, true, false, false),
+            0x1C => (MapperKind::Mbc5, false, false, false, true),
+            0x1D => (MapperKind::Mbc5, true, false, false, true),
+            0x1E => (MapperKind::Mbc5, true, true, false, true),
+            0x20 => (MapperKind::Mbc6, true, true, false, false),
+            0x22 => (MapperKind::Mbc7, true, true, false, true),
+            0xFC => (MapperKind::PocketCamera, true, true, false, false),
+            0xFD => (MapperKind::BandaiTama5, true, true, true, false),
+            0xFE => (MapperKind::HuC3, true, true, true, false),
+            0xFF => (MapperKind::HuC1, true, true, false, false),
+            _ => (MapperKind::Unknown, false, false, false, false),
+        };
+
+        CartridgeType {
+            code,
+            mapper,
+            has_ram,
+            has_battery,
+            has_timer,
+            has_rumble,
+        }
+    }
+}
+
+pub struct CartridgeHeader {
+    pub title: String,
+    pub manufacturer_code: Option<String>,
+    pub cgb_support: CgbSupport,
+    pub sgb_support: bool,
+    pub cartridge_type: CartridgeType,
+    pub rom_size_code: u8,
+    pub ram_size_code: u8,
+    pub destination_code: u8,
+    pub old_licensee_code: u8,
+    pub new_licensee_code: Option<String>,
+    pub version: u8,
+    pub header_checksum: u8,
+    pub global_checksum: u16,
+}
+
+impl CartridgeHeader {
+    // rom must be at least HEADER_END bytes long
+    pub fn parse(rom: &[u8]) -> CartridgeHeader {
+        let cgb_flag = rom[CGB_FLAG_ADDR];
+        let cgb_support = match cgb_flag {
+            0x80 => CgbSupport::Compatible,
+            0xC0 => CgbSupport::Only,
+            _ => CgbSupport::None,
+        };
+
+        // on CGB-aware carts the last title byte is the CGB flag and the four before it
+        // may hold a manufacturer code, so the title is at most 11 or 15 characters
+        let (title_end, manufacturer_code) = if cgb_support == CgbSupport::None {
+            (CGB_FLAG_ADDR + 1, None)
+        } else {
+            let code = &rom[0x013F..CGB_FLAG_ADDR];
+            if code.iter().all(|b| b.is_ascii_uppercase()) {
+                (0x013F, Some(ascii_string(code)))
+            } else {
+                (CGB_FLAG_ADDR, None)
+            }
+        };
+
+        let old_licensee_code = rom[OLD_LICENSEE_ADDR];
+        let new_licensee_code = if old_licensee_code == 0x33 {
+            Some(ascii_string(&rom[NEW_LICENSEE_ADDR..NEW_LICENSEE_ADDR + 2]))
+        } else {
+            None
+        };
+
+        CartridgeHeader {
+            title: ascii_string(&rom[TITLE_ADDR..title_end]),
+            manufacturer_code,
+            cgb_support,
+            sgb_support: rom[SGB_FLAG_ADDR] == 0x03,
+            cartridge_type: CartridgeType::from_code(rom[CARTRIDGE_TYPE_ADDR]),
+            rom_size_code: rom[ROM_SIZE_ADDR],
+            ram_size_code: rom[RAM_SIZE_ADDR],
+            destination_code: rom[DESTINATION_ADDR],
+            old_licensee_code,
+            new_licensee_code,
+            version: rom[VERSION_ADDR],
+            header_checksum: rom[HEADER_CHECKSUM_ADDR],
+            global_checksum: ((rom[GLOBAL_CHECKSUM_ADDR] as u16) << 8)
+                | rom[GLOBAL_CHECKSUM_ADDR + 1] as u16,
+        }
+    }
+
+    // ROM size in bytes as declared by the header, None for unknown codes
+    pub fn rom_size(&self) -> Option<usize> {
+        match self.rom_size_code {
+            0x00..=0x08 => Some((32 * 1024) << self.rom_size_code),
+            0x52 => Some(72 * ROM_BANK_SIZE),
+            0x53 => Some(80 * ROM_BANK_SIZE),
+            0x54 => Some(96 * ROM_BANK_SIZE),
+            _ => None,
+        }
+    }
+
+    pub fn rom_banks(&self) -> Option<usize> {
+        self.rom_size().map(|size| size / ROM_BANK_SIZE)
+    }
+
+    // external RAM size in bytes as declared by the header, None for unknown codes
+    pub fn ram_size(&self) -> Option<usize> {
+        match self.ram_size_code {
+            0x00 => Some(0),
+            0x01 => Some(2 * 1024),
+            0x02 => Some(8 * 1024),
+            0x03 => Some(32 * 1024),
+            0x04 => Some(128 * 1024),
+            0x05 => Some(64 * 1024),
+            _ => None,
+        }
+    }
+
+    pub fn compute_header_checksum(rom: &[u8]) -> u8 {
+        rom[TITLE_ADDR..HEADER_CHECKSUM_ADDR]
+            .iter()
+            .fold(0u8, |acc, &byte| acc.wrapping_sub(byte).wrapping_sub(1))
+    }
+
+    // sum of every byte in the ROM except the two global checksum bytes
+    pub fn compute_global_checksum(rom: &[u8]) -> u16 {
+        rom.iter()
+            .enumerate()
+            .filter(|&(i, _)| i != GLOBAL_CHECKSUM_ADDR && i != GLOBAL_CHECKSUM_ADDR + 1)
+            .fold(0u16, |acc, (_, &byte)| acc.wrapping_add(byte as u16))
+    }
+}
+
+fn ascii_string(bytes: &[u8]) -> String {
+    bytes
+        .iter()
+        .take_while(|&&b| b != 0)
+        .map(|&b| {
+            if b.is_ascii_graphic() || b == b' ' {
+                b as char
+            } else {
+                '?'
+            }
+        })
+        .collect::<String>()
+        .trim_end()
+        .to_string()
+}
+
+pub trait MemoryBankController {
+    // 0x0000 - 0x7FFF
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8;
+    // ROM bank currently mapped at addr, before wrapping to the ROM size
+    fn rom_bank(&self, addr: u16) -> usize {
+        if addr < 0x4000 {
+            0
+        } else {
+            1
+        }
+    }
+    // writes to 0x0000 - 0x7FFF go to the mapper's control registers
+    fn write_register(&mut self, addr: u16, value: u8);
+    // 0xA000 - 0xBFFF
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8;
+    // returns false if the write was dropped (RAM disabled or absent)
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool;
+
+    // advances mapper hardware that runs off the system clock (e.g. the MBC3 RTC)
+    fn step(&mut self, _cycles: u32) {}
+
+    fn rtc(&self) -> Option<&Rtc> {
+        None
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        None
+    }
+
+    // whether the rumble motor is currently driven
+    fn rumble(&self) -> bool {
+        false
+    }
+}
+
+// reads a byte from a 16 KiB ROM bank, wrapping the bank number to the ROM size
+pub fn read_rom_bank(rom: &[u8], bank: usize, addr: u16) -> u8 {
+    let bank_count = (rom.len() / ROM_BANK_SIZE).max(1);
+    let offset = (bank % bank_count) * ROM_BANK_SIZE + (addr as usize & (ROM_BANK_SIZE - 1));
+    rom.get(offset).copied().unwrap_or(0xFF)
+}
+
+// offset into external RAM for an 8 KiB bank, wrapping smaller RAM chips
+pub fn ram_bank_offset(ram: &[u8], bank: usize, addr: u16) -> usize {
+    (bank * RAM_BANK_SIZE + (addr - 0xA000) as usize) % ram.len()
+}
+
+pub struct RomOnly;
+
+impl MemoryBankController for RomOnly {
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8 {
+        rom.get(addr as usize).copied().unwrap_or(0xFF)
+    }
+
+    fn write_register(&mut self, _addr: u16, _value: u8) {
+        // plain ROM carts ignore writes to the ROM area
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8 {
+        if ram.is_empty() {
+            return 0xFF;
+        }
+        ram[ram_bank_offset(ram, 0, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        if ram.is_empty() {
+            return false;
+        }
+        ram[ram_bank_offset(ram, 0, addr)] = value;
+        true
+    }
+}
+
+pub struct Cartridge {
+    pub header: CartridgeHeader,
+    pub rom: Vec<u8>,
+    pub ram: Vec<u8>,
+    pub mbc: Box<dyn MemoryBankController>,
+    // set when the game writes to external RAM, cleared once saved
+    pub ram_dirty: bool,
+}
+
+impl Cartridge {
+    // validates the ROM image and picks the mapper named in its header; mappers that
+    // aren't emulated get ROM-only banking, it's up to the caller to refuse them
+    pub fn new(rom: Vec<u8>) -> Result<Cartridge> {
+        if rom.len() < MIN_ROM_SIZE {
+            return Err(Error::RomTooSmall(rom.len()));
+        }
+        if rom.len() > MAX_ROM_SIZE {
+            return Err(Error::RomTooLarge(rom.len()));
+        }
+
+        Ok(Cartridge::from_rom(rom))
+    }
+
+    // builds a cartridge without validating the header; rom must hold at least the header
+    fn from_rom(rom: Vec<u8>) -> Cartridge {
+        let header = CartridgeHeader::parse(&rom);
+
+        // MBC2 carts declare no RAM in the header since it lives inside the mapper
+        let ram_size = match header.cartridge_type.mapper {
+            MapperKind::Mbc2 => MBC2_RAM_SIZE,
+            _ if header.cartridge_type.has_ram => header.ram_size().unwrap_or(0),
+            _ => 0,
+        };
+
+        let mbc: Box<dyn MemoryBankController> = match header.cartridge_type.mapper {
+            MapperKind::Mbc1 => Box::new(Mbc1::new(&rom)),
+            MapperKind::Mbc2 => Box::new(Mbc2::new()),
+            MapperKind::Mbc3 => Box::new(Mbc3::new(header.cartridge_type.has_timer)),
+            MapperKind::Mbc5 => Box::new(Mbc5::new(header.cartridge_type.has_rumble)),
+            _ => Box::new(RomOnly),
+        };
+
+        Cartridge {
+            header,
+            rom,
+            ram: vec![0; ram_size],
+            mbc,
+            ram_dirty: false,
+        }
+    }
+
+    // a blank 32 KiB ROM-only cartridge, used before a game is loaded
+    pub fn empty() -> Cartridge {
+        Cartridge::from_rom(vec![0; MIN_ROM_SIZE])
+    }
+
+    pub fn header_checksum_valid(&self) -> bool {
+        self.check_header_checksum().is_ok()
+    }
+
+    pub fn check_header_checksum(&self) -> Result<()> {
+        let actual = CartridgeHeader::compute_header_checksum(&self.rom);
+        if actual != self.header.header_checksum {
+            return Err(Error::BadHeaderChecksum {
+                expected: self.header.header_checksum,
+                actual,
+            });
+        }
+        Ok(())
+    }
+
+    pub fn global_checksum_valid(&self) -> bool {
+        CartridgeHeader::compute_global_checksum(&self.rom) == self.header.global_checksum
+    }
+
+    pub fn read_rom(&self, addr: u16) -> u8 {
+        self.mbc.read_rom(&self.rom, addr)
+    }
+
+    // ROM bank visible at addr (0x0000 - 0x7FFF)
+    pub fn rom_bank(&self, addr: u16) -> usize {
+        let bank_count = (self.rom.len() / ROM_BANK_SIZE).max(1);
+        self.mbc.rom_bank(addr) % bank_count
+    }
+
+    pub fn write_rom(&mut self, addr: u16, value: u8) {
+        self.mbc.write_register(addr, value);
+    }
+
+    pub fn read_ram(&self, addr: u16) -> u8 {
+        self.mbc.read_ram(&self.ram, addr)
+    }
+
+    pub fn write_ram(&mut self, addr: u16, value: u8) {
+        if self.mbc.write_ram(&mut self.ram, addr, value) {
+            self.ram_dirty = true;
+        }
+    }
+
+    pub fn step(&mut self, cycles: u32) {
+        self.mbc.step(cycles);
+    }
+
+    pub fn rumble(&self) -> bool {
+        self.mbc.rumble()
+    }
+
+    // switches the RTC between emulated-cycle and host wall-time timekeeping
+    pub fn set_rtc_clock(&mut self, clock: RtcClock) {
+        if let Some(rtc) = self.mbc.rtc_mut() {
+            rtc.set_clock(clock);
+        }
+    }
+
+    pub fn has_battery(&self) -> bool {
+        self.header.cartridge_type.has_battery
+    }
+
+    // whether the save file is out of date; clocks always need saving since they keep ticking
+    pub fn save_pending(&self) -> bool {
+        self.has_battery() && (self.ram_dirty || self.mbc.rtc().is_some())
+    }
+
+    // battery-backed state in the common .sav layout: raw RAM followed by the RTC footer
+    pub fn save_data(&mut self) -> Vec<u8> {
+        let mut data = self.ram.clone();
+        if let Some(rtc) = self.mbc.rtc_mut() {
+            data.extend_from_slice(&rtc.save());
+        }
+        self.ram_dirty = false;
+        data
+    }
+
+    // returns a warning when part of the save had to be ignored
+    pub fn load_save_data(&mut self, data: &[u8]) -> Option<String> {
+        let ram_len = self.ram.len().min(data.len());
+        self.ram[..ram_len].copy_from_slice(&data[..ram_len]);
+
+        self.ram_dirty = false;
+
+        let rtc = self.mbc.rtc_mut()?;
+        let footer = &data[ram_len..];
+        if rtc.load(footer) || footer.is_empty() {
+            return None;
+        }
+        Some(format!(
+            "ignoring {} byte RTC footer, expected {}",
+            footer.len(),
+            RTC_SAVE_SIZE
+        ))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{
+        Cartridge, CartridgeHeader, CartridgeType, CgbSupport, MapperKind, HEADER_END, MIN_ROM_SIZE,
+    };
+
+    fn header_with(fields: &[(usize, &[u8])]) -> CartridgeHeader {
+        let mut rom = vec![0; HEADER_END];
+        for &(addr, bytes) in fields {
+            rom[addr..addr + bytes.len()].copy_from_slice(bytes);
+        }
+        CartridgeHeader::parse(&rom)
+    }
+
+    #[test]
+    fn title_manufacturer_and_cgb_flag() {
+        // (title area 0x0134 - 0x0143, title, manufacturer code, CGB support)
+        let cases: [(&[u8; 16], &str, Option<&str>, CgbSupport); 6] = [
+            (
+                b"SUPER MARIOLAND\0",
+                "SUPER MARIOLAND",
+                None,
+                CgbSupport::None,
+            ),
+            (
+                b"FIFTEEN CHARS!!X",
+                "FIFTEEN CHARS!!X",
+                None,
+                CgbSupport::None,
+            ),
+            (
+                b"POKEMON RED\0\0\0\0\x80",
+                "POKEMON RED",
+                None,
+                CgbSupport::Compatible,
+            ),
+            (
+                b"ZELDA DX   AZLE\x80",
+                "ZELDA DX",
+                Some("AZLE"),
+                CgbSupport::Compatible,
+            ),
+            // not a manufacturer code, so the title runs up to the CGB flag
+            (
+                b"GOLF COURSE 200\x80",
+                "GOLF COURSE 200",
+                None,
+                CgbSupport::Compatible,
+            ),
+            (
+                b"POKEMON CRYBYTE\xC0",
+                "POKEMON CRY",
+                Some("BYTE"),
+                CgbSupport::Only,
+            ),
+        ];
+        for (bytes, title, manufacturer, cgb) in cases {
+            let header = header_with(&[(0x0134, bytes)]);
+            assert_eq!(header.title, title);
+            assert_eq!(
+                header.manufacturer_code.as_deref(),
+                manufacturer,
+                "{}",
+                title
+            );
+            assert_eq!(header.cgb_support, cgb, "{}", title);
+        }
+    }
+
+    #[test]
+    fn licensee_sgb_and_global_checksum_fields() {
+        let header = header_with(&[
+            (0x0144, b"01"),
+            (0x0146, &[0x03]),
+            (0x014B, &[0x33]),
+            (0x014E, &[0x12, 0x34]),
+        ]);
+        assert_eq!(header.new_licensee_code.as_deref(), Some("01"));
+        assert!(header.sgb_support);
+        assert_eq!(header.global_checksum, 0x1234);
+
+        let header = header_with(&[(0x0144, b"01"), (0x014B, &[0x01])]);
+        assert_eq!(header.new_licensee_code, None);
+        assert!(!header.sgb_support);
+    }
+
+    #[test]
+    fn rom_size_codes() {
+        let cases = [
+            (0x00, Some(2)),
+            (0x01, Some(4)),
+            (0x02, Some(8)),
+            (0x03, Some(16)),
+            (0x04, Some(32)),
+            (0x05, Some(64)),
+            (0x06, Some(128)),
+            (0x07, Some(256)),
+            (0x08, Some(512)),
+            (0x52, Some(72)),
+            (0x53, Some(80)),
+            (0x54, Some(96)),
+            (0x09, None),
+            (0xFF, None),
+        ];
+        for (code, banks) in cases {
+            let header = header_with(&[(0x0148, &[code])]);
+            assert_eq!(header.rom_banks(), banks, "code {:02X}", code);
+        }
+    }
+
+    #[test]
+    fn ram_size_codes() {
+        let cases = [
+            (0x00, Some(0)),
+            (0x01, Some(0x800)),
+            (0x02, Some(0x2000)),
+            (0x03, Some(0x8000)),
+            (0x04, Some(0x20000)),
+            (0x05, Some(0x10000)),
+            (0x06, None),
+        ];
+        for (code, size) in cases {
+            let header = header_with(&[(0x0149, &[code])]);
+            assert_eq!(header.ram_size(), size, "code {:02X}", code);
+        }
+    }
+
+    #[test]
+    fn cartridge_type_codes() {
+        let mbc3 = CartridgeType::from_code(0x10);
+        assert_eq!(mbc3.mapper, MapperKind::Mbc3);
+        assert!(mbc3.has_ram && mbc3.has_battery && mbc3.has_timer && !mbc3.has_rumble);
+
+        let rumble = CartridgeType::from_code(0x1C);
+        assert_eq!(rumble.mapper, MapperKind::Mbc5);
+        assert!(rumble.has_rumble && !rumble.has_ram);
+
+        for code in [0x04, 0x07, 0x0E, 0x14, 0x21, 0x80, 0xFB] {
+            let unknown = CartridgeType::from_code(code);
+            assert_eq!(unknown.mapper, MapperKind::Unknown, "code {:02X}", code);
+            assert_eq!(unknown.code, code);
+            assert!(!unknown.mapper.is_supported());
+        }
+    }
+
+    #[test]
+    fn tetris_header_checksum() {
+        // Tetris (World) (Rev 1)
+        let mut rom = vec![0; HEADER_END];
+        rom[0x0134..0x013A].copy_from_slice(b"TETRIS");
+        rom[0x014B] = 0x01;
+        rom[0x014C] = 0x01;
+        assert_eq!(CartridgeHeader::compute_header_checksum(&rom), 0x0A);
+    }
+
+    #[test]
+    fn global_checksum_skips_its_own_bytes() {
+        let mut rom = vec![0; HEADER_END];
+        rom[0x0000] = 0xFF;
+        rom[0x0100] = 0x02;
+        rom[0x014E] = 0xAA;
+        rom[0x014F] = 0xBB;
+        assert_eq!(CartridgeHeader::compute_global_checksum(&rom), 0x0101);
+    }
+
+    #[test]
+    fn only_stored_ram_writes_mark_it_dirty() {
+        // MBC1+RAM+BATTERY with 8 KiB of RAM
+        let mut rom = vec![0; MIN_ROM_SIZE];
+        rom[0x0147] = 0x03;
+        rom[0x0149] = 0x02;
+        let mut cartridge = Cartridge::from_rom(rom);
+
+        cartridge.write_ram(0xA000, 0x42);
+        assert!(!cartridge.ram_dirty);
+        assert!(!cartridge.save_pending());
+
+        cartridge.write_rom(0x0000, 0x0A);
+        cartridge.write_ram(0xA000, 0x42);
+        assert!(cartridge.ram_dirty);
+        assert!(cartridge.save_pending());
+    }
+}
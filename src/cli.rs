@@ -0,0 +1,388 @@
+use std::ops::RangeInclusive;
+use std::path::PathBuf;
+
+use lr35902_emulator::{mbc3::RtcClock, trace::TraceFilter, Model};
+
+pub const USAGE: &str = "\
+usage: lr35902-emulator [options] <rom>
+       lr35902-emulator disasm [disasm options] <rom>
+
+options:
+  --scale <n>          window scale factor (default 4)
+  --boot-rom <path>    run this boot ROM before the cartridge
+  --strict-header      refuse ROMs with a bad header checksum instead of warning
+  --lenient-mapper     run carts with a mapper that isn't emulated as ROM only
+  --model <model>      hardware model: dmg0, dmg (default), mgb, sgb, sgb2, cgb, agb
+  --headless           run without a window or audio output
+  --frames <n>         stop after n frames
+  --save-dir <dir>     directory for battery save files (default: next to the ROM)
+  --rtc <clock>        MBC3 clock source: emulated (default) or host wall time
+  --break <addr>       stop when PC reaches addr (hex, repeatable) and dump the CPU state
+  --trace <path>       log every instruction in Gameboy Doctor format
+  --trace-pc <a>-<b>   only trace PCs from a to b (hex, repeatable)
+  --trace-bank <n>     only trace code running from ROM bank n (repeatable)
+  --trace-lines <n>    stop tracing after n lines
+  --trace-doctor       read LY as 0x90 while tracing, as Gameboy Doctor expects
+  -h, --help           print this message
+
+disasm options:
+  --bank <n>[-<m>]     only ROM bank n, or banks n to m (default: every bank)
+  --start <addr>       first address to decode in each bank (hex)
+  --end <addr>         stop before this address in each bank (hex)
+  --output <path>      write the listing to a file instead of stdout";
+
+const DEFAULT_SCALE: usize = 4;
+const MAX_SCALE: usize = 16;
+
+pub struct Options {
+    pub rom_path: PathBuf,
+    pub scale: usize,
+    pub boot_rom: Option<PathBuf>,
+    pub strict_header: bool,
+    pub allow_unsupported_mapper: bool,
+    pub model: Model,
+    pub headless: bool,
+    pub frames: Option<u64>,
+    pub save_dir: Option<PathBuf>,
+    pub rtc_clock: RtcClock,
+    pub breakpoints: Vec<u16>,
+    pub trace: Option<PathBuf>,
+    pub trace_filter: TraceFilter,
+    pub trace_doctor: bool,
+}
+
+pub struct DisasmOptions {
+    pub rom_path: PathBuf,
+    pub banks: Option<RangeInclusive<u16>>,
+    pub start: u16,
+    pub end: u16,
+    pub output: Option<PathBuf>,
+}
+
+pub enum Command {
+    Run(Options),
+    Disasm(DisasmOptions),
+    Help,
+}
+
+// parses the arguments after the program name; errors are ready to show the user
+pub fn parse<I: IntoIterator<Item = String>>(args: I) -> Result<Command, String> {
+    let mut args = args.into_iter().peekable();
+    if args.peek().is_some_and(|arg| arg == "disasm") {
+        args.next();
+        return parse_disasm(args);
+    }
+
+    let mut rom_path = None;
+    let mut scale = DEFAULT_SCALE;
+    let mut boot_rom = None;
+    let mut strict_header = false;
+    let mut allow_unsupported_mapper = false;
+    let mut model = Model::default();
+    let mut headless = false;
+    let mut frames = None;
+    let mut save_dir = None;
+    let mut rtc_clock = RtcClock::Emulated;
+    let mut breakpoints = Vec::new();
+    let mut trace = None;
+    let mut trace_filter = TraceFilter::default();
+    let mut trace_doctor = false;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--scale" => {
+                let value = value(&mut args, &arg)?;
+                scale = match value.parse() {
+                    Ok(n @ 1..=MAX_SCALE) => n,
+                    _ => {
+                        return Err(format!(
+                            "invalid scale '{}', expected a whole number from 1 to {}",
+                            value, MAX_SCALE
+                        ))
+                    }
+                };
+            }
+            "--boot-rom" => boot_rom = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--strict-header" => strict_header = true,
+            "--lenient-mapper" => allow_unsupported_mapper = true,
+            "--model" => {
+                let value = value(&mut args, &arg)?;
+                model = Model::from_name(&value).ok_or_else(|| {
+                    let names: Vec<&str> = Model::ALL.iter().map(|model| model.name()).collect();
+                    format!(
+                        "unknown model '{}', expected one of: {}",
+                        value,
+                        names.join(", ")
+                    )
+                })?;
+            }
+            "--headless" => headless = true,
+            "--frames" => {
+                let value = value(&mut args, &arg)?;
+                frames = Some(value.parse().map_err(|_| {
+                    format!("invalid frame count '{}', expected a whole number", value)
+                })?);
+            }
+            "--save-dir" => {
+                let dir = PathBuf::from(value(&mut args, &arg)?);
+                if !dir.is_dir() {
+                    return Err(format!("save directory {} does not exist", dir.display()));
+                }
+                save_dir = Some(dir);
+            }
+            "--rtc" => {
+                let value = value(&mut args, &arg)?;
+                rtc_clock = match value.to_ascii_lowercase().as_str() {
+                    "emulated" => RtcClock::Emulated,
+                    "host" => RtcClock::Host,
+                    _ => {
+                        return Err(format!(
+                            "unknown clock '{}', expected emulated or host",
+                            value
+                        ))
+                    }
+                };
+            }
+            "--break" => {
+                let value = value(&mut args, &arg)?;
+                breakpoints.push(parse_address(&value)?);
+            }
+            "--trace" => trace = Some(PathBuf::from(value(&mut args, &arg)?)),
+            "--trace-pc" => {
+                let value = value(&mut args, &arg)?;
+                trace_filter.pc_ranges.push(parse_address_range(&value)?);
+            }
+            "--trace-bank" => {
+                let value = value(&mut args, &arg)?;
+                trace_filter.banks.push(
+                    value
+                        .parse()
+                        .map_err(|_| format!("invalid bank '{}', expected a bank number", value))?,
+                );
+            }
+            "--trace-lines" => {
+                let value = value(&mut args, &arg)?;
+                trace_filter.max_lines = Some(value.parse().map_err(|_| {
+                    format!("invalid line count '{}', expected a whole number", value)
+                })?);
+            }
+            "--trace-doctor" => trace_doctor = true,
+            _ if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+            _ => {
+                if rom_path.is_some() {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                rom_path = Some(PathBuf::from(arg));
+            }
+        }
+    }
+
+    let rom_path = check_rom_path(rom_path)?;
+    if let Some(boot_rom) = &boot_rom {
+        if !boot_rom.is_file() {
+            return Err(format!("boot ROM {} not found", boot_rom.display()));
+        }
+    }
+
+    Ok(Command::Run(Options {
+        rom_path,
+        scale,
+        boot_rom,
+        strict_header,
+        allow_unsupported_mapper,
+        model,
+        headless,
+        frames,
+        save_dir,
+        rtc_clock,
+        breakpoints,
+        trace,
+        trace_filter,
+        trace_doctor,
+    }))
+}
+
+fn parse_disasm(mut args: impl Iterator<Item = String>) -> Result<Command, String> {
+    let mut rom_path = None;
+    let mut banks = None;
+    let mut start = 0x0000;
+    let mut end = 0x8000;
+    let mut output = None;
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Command::Help),
+            "--bank" => banks = Some(parse_banks(&value(&mut args, &arg)?)?),
+            "--start" => start = parse_address(&value(&mut args, &arg)?)?,
+            // an exclusive end, so 0x8000 (the default) has to fit
+            "--end" => end = parse_address(&value(&mut args, &arg)?)?,
+            "--output" => output = Some(PathBuf::from(value(&mut args, &arg)?)),
+            _ if arg.starts_with('-') => return Err(format!("unknown option '{}'", arg)),
+            _ => {
+                if rom_path.is_some() {
+                    return Err(format!("unexpected argument '{}'", arg));
+                }
+                rom_path = Some(PathBuf::from(arg));
+            }
+        }
+    }
+
+    if start >= end {
+        return Err(format!(
+            "empty range: start {:04X} is not before end {:04X}",
+            start, end
+        ));
+    }
+
+    Ok(Command::Disasm(DisasmOptions {
+        rom_path: check_rom_path(rom_path)?,
+        banks,
+        start,
+        end,
+        output,
+    }))
+}
+
+fn check_rom_path(rom_path: Option<PathBuf>) -> Result<PathBuf, String> {
+    let Some(rom_path) = rom_path else {
+        return Err("no ROM file given".to_string());
+    };
+    if !rom_path.is_file() {
+        return Err(format!("ROM file {} not found", rom_path.display()));
+    }
+    Ok(rom_path)
+}
+
+// accepts 3 and 1-7
+fn parse_banks(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let invalid = || {
+        format!(
+            "invalid bank '{}', expected a bank number or a range like 1-7",
+            value
+        )
+    };
+    let (first, last) = value.split_once('-').unwrap_or((value, value));
+    let first: u16 = first.parse().map_err(|_| invalid())?;
+    let last: u16 = last.parse().map_err(|_| invalid())?;
+    if first > last {
+        return Err(invalid());
+    }
+    Ok(first..=last)
+}
+
+// accepts 0150-01FF, both ends included
+fn parse_address_range(value: &str) -> Result<RangeInclusive<u16>, String> {
+    let (start, end) = value.split_once('-').ok_or_else(|| {
+        format!(
+            "invalid address range '{}', expected two hex addresses like 0150-01FF",
+            value
+        )
+    })?;
+    let (start, end) = (parse_address(start)?, parse_address(end)?);
+    if start > end {
+        return Err(format!("address range '{}' is backwards", value));
+    }
+    Ok(start..=end)
+}
+
+fn value(args: &mut impl Iterator<Item = String>, option: &str) -> Result<String, String> {
+    args.next()
+        .ok_or_else(|| format!("option '{}' needs a value", option))
+}
+
+// accepts 0150, 0x0150 and $0150
+fn parse_address(value: &str) -> Result<u16, String> {
+    let digits = value
+        .strip_prefix("0x")
+        .or_else(|| value.strip_prefix("0X"))
+        .or_else(|| value.strip_prefix('$'))
+        .unwrap_or(value);
+    u16::from_str_radix(digits, 16).map_err(|_| {
+        format!(
+            "invalid address '{}', expected a hex value like 0x0150",
+            value
+        )
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use lr35902_emulator::{mbc3::RtcClock, Model};
+
+    use super::{parse, Command, Options};
+
+    // any existing file will do, the parser only checks that the ROM is there
+    const ROM: &str = concat!(env!("CARGO_MANIFEST_DIR"), "/Cargo.toml");
+
+    fn parse_run(args: &[&str]) -> Result<Options, String> {
+        match parse(args.iter().map(|arg| arg.to_string()))? {
+            Command::Run(options) => Ok(options),
+            _ => panic!("expected a run command for {:?}", args),
+        }
+    }
+
+    #[test]
+    fn defaults() {
+        let options = parse_run(&[ROM]).unwrap();
+        assert_eq!(options.rom_path.to_str(), Some(ROM));
+        assert_eq!(options.scale, 4);
+        assert_eq!(options.model, Model::Dmg);
+        assert_eq!(options.rtc_clock, RtcClock::Emulated);
+        assert!(!options.headless && !options.strict_header);
+        assert!(options.breakpoints.is_empty());
+    }
+
+    #[test]
+    fn rtc_clock_source() {
+        let cases = [
+            ("emulated", RtcClock::Emulated),
+            ("host", RtcClock::Host),
+            ("Host", RtcClock::Host),
+        ];
+        for (value, clock) in cases {
+            assert_eq!(parse_run(&["--rtc", value, ROM]).unwrap().rtc_clock, clock);
+        }
+
+        assert_eq!(
+            parse_run(&[ROM, "--rtc", "wall"]).err().unwrap(),
+            "unknown clock 'wall', expected emulated or host"
+        );
+        assert_eq!(
+            parse_run(&[ROM, "--rtc"]).err().unwrap(),
+            "option '--rtc' needs a value"
+        );
+    }
+
+    #[test]
+    fn options_with_values() {
+        let options = parse_run(&[
+            "--scale", "2", "--model", "CGB", "--frames", "60", "--break", "0x0150", "--break",
+            "$C000", ROM,
+        ])
+        .unwrap();
+        assert_eq!(options.scale, 2);
+        assert_eq!(options.model, Model::Cgb);
+        assert_eq!(options.frames, Some(60));
+        assert_eq!(options.breakpoints, [0x0150, 0xC000]);
+
+        assert!(parse_run(&["--scale", "0", ROM]).is_err());
+        assert!(parse_run(&["--model", "gba", ROM]).is_err());
+        assert!(parse_run(&["--break", "xyz", ROM]).is_err());
+    }
+
+    #[test]
+    fn bad_arguments() {
+        assert_eq!(parse_run(&[]).err().unwrap(), "no ROM file given");
+        assert_eq!(
+            parse_run(&["--fast", ROM]).err().unwrap(),
+            "unknown option '--fast'"
+        );
+        assert_eq!(
+            parse_run(&[ROM, ROM]).err().unwrap(),
+            format!("unexpected argument '{}'", ROM)
+        );
+        assert!(parse_run(&["missing.gb"]).is_err());
+        assert!(matches!(parse(["--help".to_string()]), Ok(Command::Help)));
+    }
+}
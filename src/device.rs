@@ -1,18 +1,66 @@
-use std::{cell::RefCell, fs::File, io::Read, rc::Rc};
+use std::{
+    cell::{Ref, RefCell},
+    fs, io,
+    path::{Path, PathBuf},
+    rc::Rc,
+};
 
-use crate::{cpu::CPU, memory::Memory, ppu::PPU};
+use crate::{
+    apu,
+    cartridge::Cartridge,
+    cpu::CPU,
+    error::{Error, Result},
+    joypad::Buttons,
+    mbc3::RtcClock,
+    memory::{Memory, CGB_BOOT_ROM_SIZE, DMG_BOOT_ROM_SIZE},
+    model::Model,
+    ppu::PPU,
+    registers::Registers,
+    trace::{TraceFilter, Tracer},
+    wav::AudioRecorder,
+};
 
 pub type SharedMemory = Rc<RefCell<Memory>>;
 
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum DeviceEvent {
+    // the cartridge switched its rumble motor on (true) or off (false)
+    Rumble(bool),
+    // run_frame stopped before executing the instruction at this address
+    Breakpoint(u16),
+    // the CPU hit one of the unused opcodes and locked up
+    IllegalOpcode { opcode: u8, pc: u16 },
+    // something the emulator recovered from but the user should hear about
+    Warning(String),
+}
+
 pub struct Device {
     pub cpu: CPU,
     pub ppu: Rc<RefCell<PPU>>,
     pub memory: SharedMemory,
+    pub rtc_clock: RtcClock,
+    // where battery-backed RAM is persisted, None for carts without a battery
+    pub save_path: Option<PathBuf>,
+    // directory for .sav files, defaults to the ROM's own directory
+    pub save_dir: Option<PathBuf>,
+    pub breakpoints: Vec<u16>,
+    pub model: Model,
+    // refuse a ROM with a bad header checksum instead of warning and running it anyway
+    pub strict_header: bool,
+    // run carts with a mapper that isn't emulated as ROM only instead of refusing them
+    pub allow_unsupported_mapper: bool,
 }
 
 impl Device {
     pub fn new() -> Device {
-        let memory = Rc::new(RefCell::new(Memory::new()));
+        Device::with_model(Model::default())
+    }
+
+    pub fn with_model(model: Model) -> Device {
+        let mut memory = Memory::new();
+        memory.set_model(model);
+
+        let memory = Rc::new(RefCell::new(memory));
         let ppu = Rc::new(RefCell::new(PPU::new(memory.clone())));
         let cpu = CPU::new(memory.clone(), ppu.clone());
 
@@ -20,19 +68,278 @@ impl Device {
             memory: memory.clone(),
             cpu,
             ppu,
+            rtc_clock: RtcClock::Emulated,
+            save_path: None,
+            save_dir: None,
+            breakpoints: Vec::new(),
+            model,
+            strict_header: false,
+            allow_unsupported_mapper: false,
+        }
+    }
+
+    // loads a ROM file and, for battery-backed carts, its save file if there is one
+    pub fn load_instructions(&mut self, filename: impl AsRef<Path>) -> Result<()> {
+        let rom_path = filename.as_ref();
+        let rom = fs::read(rom_path).map_err(|e| Error::io(rom_path, e))?;
+
+        self.load_rom(rom)?;
+
+        let mut memory = self.memory.borrow_mut();
+        if memory.cartridge.has_battery() {
+            let save_path = match (&self.save_dir, rom_path.file_name()) {
+                (Some(dir), Some(name)) => dir.join(name).with_extension("sav"),
+                _ => rom_path.with_extension("sav"),
+            };
+            if save_path.exists() {
+                let data = fs::read(&save_path).map_err(|e| Error::io(&save_path, e))?;
+                if let Some(warning) = memory.cartridge.load_save_data(&data) {
+                    memory.warn(warning);
+                }
+            }
+            self.save_path = Some(save_path);
+        }
+
+        Ok(())
+    }
+
+    // inserts a cartridge from an in-memory ROM image; nothing is persisted to disk
+    pub fn load_rom(&mut self, rom: Vec<u8>) -> Result<()> {
+        let mut cartridge = Cartridge::new(rom)?;
+        cartridge.set_rtc_clock(self.rtc_clock);
+
+        let cartridge_type = cartridge.header.cartridge_type;
+        let mut mapper_error = None;
+        if !cartridge_type.mapper.is_supported() {
+            let err = Error::UnsupportedMapper {
+                code: cartridge_type.code,
+                mapper: cartridge_type.mapper,
+            };
+            if !self.allow_unsupported_mapper {
+                return Err(err);
+            }
+            mapper_error = Some(err);
+        }
+
+        // a mapped boot ROM validates the header itself and locks up on a bad one;
+        // without one, start from the state the model's boot ROM would leave
+        let boot_rom_mapped = self.memory.borrow().boot_rom.is_some();
+        let mut header_error = None;
+        if !boot_rom_mapped {
+            if let Err(err) = cartridge.check_header_checksum() {
+                if self.strict_header {
+                    return Err(err);
+                }
+                header_error = Some(err);
+            }
+            self.cpu.registers = self.model.post_boot_registers(&cartridge);
+        }
+
+        self.save_path = None;
+        let mut memory = self.memory.borrow_mut();
+        if let Some(err) = header_error {
+            memory.warn(err.to_string());
         }
+        if let Some(err) = mapper_error {
+            memory.warn(format!("{}, running it as ROM only", err));
+        }
+        if !cartridge.global_checksum_valid() {
+            memory.warn("cartridge global checksum mismatch".to_string());
+        }
+        memory.cartridge = cartridge;
+        if !boot_rom_mapped {
+            memory.apply_post_boot();
+        }
+        Ok(())
+    }
+
+    pub fn load_boot_rom(&mut self, filename: impl AsRef<Path>) -> Result<()> {
+        let path = filename.as_ref();
+        let boot_rom = fs::read(path).map_err(|e| Error::io(path, e))?;
+        self.set_boot_rom(boot_rom)
     }
 
-    pub fn load_instructions(&mut self, filename: &str) {
-        let mut file = File::open(filename).expect("error trying to open the file");
-        let mut buffer = Vec::new();
-        file.read_to_end(&mut buffer).expect("error trying to read");
+    // maps a boot ROM over the cartridge and resets the CPU to its power-on state so the
+    // boot ROM runs first; call before load_rom to let it do the header check
+    pub fn set_boot_rom(&mut self, boot_rom: Vec<u8>) -> Result<()> {
+        if boot_rom.len() != DMG_BOOT_ROM_SIZE && boot_rom.len() != CGB_BOOT_ROM_SIZE {
+            return Err(Error::BootRomSize(boot_rom.len()));
+        }
+
+        self.memory.borrow_mut().boot_rom = Some(boot_rom);
+        self.cpu.registers = Registers::power_on();
+        Ok(())
+    }
+
+    // runs a single instruction (plus any interrupt dispatch) and returns the T-cycles taken
+    pub fn step(&mut self) -> u32 {
+        self.cpu.step()
+    }
 
-        let start_address = 0x0000;
-        for (i, &byte) in buffer.iter().enumerate() {
+    // runs one frame's worth of cycles, returns true if the PPU finished a new frame;
+    // hitting a breakpoint ends the call early with a DeviceEvent::Breakpoint
+    pub fn run_frame(&mut self) -> bool {
+        if let Some(pc) = self.cpu.cycle_until(&self.breakpoints) {
             self.memory
                 .borrow_mut()
-                .write_byte((start_address + i) as u16, byte);
+                .events
+                .push(DeviceEvent::Breakpoint(pc));
         }
+
+        let mut ppu = self.ppu.borrow_mut();
+        std::mem::replace(&mut ppu.frame_ready, false)
+    }
+
+    // ARGB pixels, ppu::SCREEN_WIDTH x ppu::SCREEN_HEIGHT, row by row
+    pub fn framebuffer(&self) -> Ref<'_, [u32]> {
+        Ref::map(self.ppu.borrow(), |ppu| &ppu.framebuffer[..])
+    }
+
+    // writes battery-backed RAM (and RTC) to the save file if anything changed
+    pub fn write_save(&mut self) -> Result<()> {
+        let Some(save_path) = &self.save_path else {
+            return Ok(());
+        };
+
+        let mut memory = self.memory.borrow_mut();
+        if !memory.cartridge.save_pending() {
+            return Ok(());
+        }
+
+        fs::write(save_path, memory.cartridge.save_data()).map_err(|e| Error::io(save_path, e))
+    }
+
+    // MBC3 clocks follow emulated cycles by default; Host keeps them in step with wall time
+    pub fn set_rtc_clock(&mut self, clock: RtcClock) {
+        self.rtc_clock = clock;
+        self.memory.borrow_mut().cartridge.set_rtc_clock(clock);
+    }
+
+    // replaces the full button state; pressing a button raises the joypad interrupt
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        self.memory.borrow_mut().set_buttons(buttons);
+    }
+
+    // interleaved stereo samples at apu::SAMPLE_RATE produced since the last call
+    pub fn take_audio_samples(&mut self) -> Vec<f32> {
+        self.memory.borrow_mut().apu.take_samples()
+    }
+
+    // records the stereo mix to a WAV file at `path`; with `per_channel` each channel is
+    // also written to <name>_ch1.wav ... <name>_ch4.wav beside it
+    pub fn start_recording(&mut self, path: &Path, per_channel: bool) -> io::Result<()> {
+        let recorder = AudioRecorder::create(path, per_channel, apu::SAMPLE_RATE)?;
+        // replacing a running recording finishes its files on drop
+        self.memory.borrow_mut().apu.recorder = Some(recorder);
+        Ok(())
+    }
+
+    // finishes the WAV headers; does nothing if no recording is running
+    pub fn stop_recording(&mut self) -> io::Result<()> {
+        match self.memory.borrow_mut().apu.recorder.take() {
+            Some(mut recorder) => recorder.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn is_recording(&self) -> bool {
+        self.memory.borrow().apu.recorder.is_some()
+    }
+
+    // logs every instruction to a Gameboy Doctor style trace until stopped
+    pub fn start_trace(&mut self, path: &Path, filter: TraceFilter) -> io::Result<()> {
+        self.cpu.tracer = Some(Tracer::create(path, filter)?);
+        Ok(())
+    }
+
+    // stubs LY to 0x90 so a trace of a CPU test lines up with Gameboy Doctor's logs
+    pub fn set_doctor_mode(&mut self, doctor: bool) {
+        self.memory.borrow_mut().doctor = doctor;
+    }
+
+    pub fn stop_trace(&mut self) -> io::Result<()> {
+        match self.cpu.tracer.take() {
+            Some(mut tracer) => tracer.finish(),
+            None => Ok(()),
+        }
+    }
+
+    pub fn rumble(&self) -> bool {
+        self.memory.borrow().cartridge.rumble()
+    }
+
+    // events raised since the last call, oldest first
+    pub fn take_events(&mut self) -> Vec<DeviceEvent> {
+        std::mem::take(&mut self.memory.borrow_mut().events)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Device, DeviceEvent};
+    use crate::error::Error;
+
+    // 32 KiB ROM with a valid header checksum and a zeroed global checksum
+    fn test_rom(cartridge_type: u8) -> Vec<u8> {
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = cartridge_type;
+        rom[0x14D] = rom[0x134..=0x14C]
+            .iter()
+            .fold(0u8, |sum, &byte| sum.wrapping_sub(byte).wrapping_sub(1));
+        rom
+    }
+
+    #[test]
+    fn bad_header_checksum_is_a_warning_unless_strict() {
+        let mut rom = test_rom(0x00);
+        rom[0x14D] ^= 0xFF;
+
+        let mut gb = Device::new();
+        gb.load_rom(rom.clone()).unwrap();
+        assert!(matches!(
+            gb.take_events().first(),
+            Some(DeviceEvent::Warning(message)) if message.contains("header checksum")
+        ));
+
+        let mut gb = Device::new();
+        gb.strict_header = true;
+        assert!(matches!(
+            gb.load_rom(rom),
+            Err(Error::BadHeaderChecksum { .. })
+        ));
+    }
+
+    #[test]
+    fn unsupported_mapper_is_refused() {
+        let mut gb = Device::new();
+        // HuC1
+        assert!(matches!(
+            gb.load_rom(test_rom(0xFF)),
+            Err(Error::UnsupportedMapper { code: 0xFF, .. })
+        ));
+    }
+
+    #[test]
+    fn unsupported_mapper_runs_as_rom_only_when_allowed() {
+        let mut gb = Device::new();
+        gb.allow_unsupported_mapper = true;
+        gb.load_rom(test_rom(0xFF)).unwrap();
+        assert!(matches!(
+            gb.take_events().first(),
+            Some(DeviceEvent::Warning(message)) if message.contains("running it as ROM only")
+        ));
+        assert_eq!(gb.memory.borrow().cartridge.rom_bank(0x4000), 1);
+    }
+
+    #[test]
+    fn global_checksum_mismatch_is_a_warning() {
+        let mut gb = Device::new();
+        gb.load_rom(test_rom(0x00)).unwrap();
+        assert_eq!(
+            gb.take_events(),
+            [DeviceEvent::Warning(
+                "cartridge global checksum mismatch".to_string()
+            )]
+        );
     }
 }
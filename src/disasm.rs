@@ -0,0 +1,254 @@
+use std::collections::BTreeSet;
+use std::fmt::Write;
+use std::ops::RangeInclusive;
+
+use crate::{
+    cartridge::{read_rom_bank, ROM_BANK_SIZE},
+    memory::Memory,
+    opcodes::{Instruction, Operand, CB_PREFIXED, UNPREFIXED},
+};
+
+/*
+Linear-sweep disassembler producing RGBDS syntax.
+
+Addresses in 0x4000 - 0x7FFF are ambiguous without a bank, so everything is keyed
+by (bank, address): bank 0 below 0x4000, the mapped ROM bank in 0x4000 - 0x7FFF,
+and bank 0 again for RAM and I/O. Jump, call and RST targets that land inside the
+disassembled range become labels named L<bank>_<address>.
+*/
+
+// anything code can be read from, with the ROM bank mapped at each address
+pub trait CodeSource {
+    fn read_byte(&self, addr: u16) -> u8;
+    fn bank(&self, addr: u16) -> u16;
+}
+
+impl CodeSource for Memory {
+    fn read_byte(&self, addr: u16) -> u8 {
+        Memory::read_byte(self, addr)
+    }
+
+    fn bank(&self, addr: u16) -> u16 {
+        match addr {
+            0x0000..=0x7FFF => self.cartridge.rom_bank(addr) as u16,
+            _ => 0,
+        }
+    }
+}
+
+// a ROM image with one bank mapped at 0x4000, as a mapper would
+pub struct RomBank<'a> {
+    pub rom: &'a [u8],
+    pub bank: u16,
+}
+
+impl<'a> RomBank<'a> {
+    pub fn new(rom: &'a [u8], bank: u16) -> RomBank<'a> {
+        RomBank { rom, bank }
+    }
+
+    pub fn bank_count(rom: &[u8]) -> u16 {
+        rom.len().div_ceil(ROM_BANK_SIZE).max(1) as u16
+    }
+}
+
+impl CodeSource for RomBank<'_> {
+    fn read_byte(&self, addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(self.rom, 0, addr),
+            0x4000..=0x7FFF => read_rom_bank(self.rom, self.bank as usize, addr),
+            _ => 0xFF,
+        }
+    }
+
+    fn bank(&self, addr: u16) -> u16 {
+        match addr {
+            0x4000..=0x7FFF => self.bank,
+            _ => 0,
+        }
+    }
+}
+
+pub struct Decoded {
+    pub bank: u16,
+    pub addr: u16,
+    pub bytes: Vec<u8>,
+    pub instruction: &'static Instruction,
+    // raw operand: n8/e8/a8 in the low byte, or n16/a16
+    pub operand: u16,
+    // (bank, address) a jump, call or RST goes to
+    pub target: Option<(u16, u16)>,
+}
+
+impl Decoded {
+    pub fn is_truncated(&self) -> bool {
+        self.bytes.len() < self.instruction.length as usize
+    }
+
+    // RGBDS source for the instruction, with targets resolved through `label`
+    pub fn format(&self, label: impl Fn(u16, u16) -> Option<String>) -> String {
+        let instruction = self.instruction;
+        if instruction.is_illegal() {
+            return format!("db ${:02X}", self.bytes[0]);
+        }
+
+        let mnemonic = instruction.mnemonic;
+        let target = match self.target {
+            Some((bank, addr)) => label(bank, addr).unwrap_or_else(|| format!("${:04X}", addr)),
+            None => format!("${:04X}", self.operand),
+        };
+
+        match instruction.operand {
+            // RST keeps its vector: RGBDS wants a constant there
+            Operand::None => mnemonic.to_string(),
+            Operand::Imm8 => mnemonic.replace("n8", &format!("${:02X}", self.operand)),
+            Operand::Imm16 => mnemonic.replace("n16", &format!("${:04X}", self.operand)),
+            Operand::Offset8 => {
+                let offset = self.operand as u8 as i8;
+                if offset < 0 && mnemonic.contains("+ e8") {
+                    mnemonic.replace("+ e8", &format!("- {}", offset.unsigned_abs()))
+                } else {
+                    mnemonic.replace("e8", &offset.to_string())
+                }
+            }
+            Operand::HighAddr8 => {
+                mnemonic.replace("a8", &format!("${:04X}", 0xFF00 | self.operand))
+            }
+            Operand::Addr16 => mnemonic.replace("a16", &target),
+            Operand::Relative8 => mnemonic.replace("e8", &target),
+        }
+    }
+}
+
+// decodes the instruction at addr; bytes past 0xFFFF wrap around like PC does
+pub fn decode(source: &impl CodeSource, addr: u16) -> Decoded {
+    let bank = source.bank(addr);
+    let opcode = source.read_byte(addr);
+
+    let instruction = if opcode == 0xCB {
+        &CB_PREFIXED[source.read_byte(addr.wrapping_add(1)) as usize]
+    } else {
+        &UNPREFIXED[opcode as usize]
+    };
+
+    let bytes: Vec<u8> = (0..instruction.length as u16)
+        .map(|i| source.read_byte(addr.wrapping_add(i)))
+        .collect();
+    let operand = match instruction.operand {
+        Operand::None => 0,
+        Operand::Imm16 | Operand::Addr16 => (bytes[2] as u16) << 8 | bytes[1] as u16,
+        _ => bytes[1] as u16,
+    };
+
+    let next = addr.wrapping_add(instruction.length as u16);
+    let target_addr = match (opcode, instruction.operand) {
+        (0xC7 | 0xCF | 0xD7 | 0xDF | 0xE7 | 0xEF | 0xF7 | 0xFF, _) => Some((opcode & 0x38) as u16),
+        (_, Operand::Relative8) => Some(next.wrapping_add(operand as u8 as i8 as u16)),
+        (_, Operand::Addr16)
+            if instruction.mnemonic.starts_with("JP")
+                || instruction.mnemonic.starts_with("CALL") =>
+        {
+            Some(operand)
+        }
+        _ => None,
+    };
+    let target = target_addr.map(|target| (source.bank(target), target));
+
+    Decoded {
+        bank,
+        addr,
+        bytes,
+        instruction,
+        operand,
+        target,
+    }
+}
+
+// decodes start..end in order, treating every byte as code
+pub fn sweep(source: &impl CodeSource, start: u16, end: u16) -> Vec<Decoded> {
+    let mut decoded = Vec::new();
+    let mut addr = start as u32;
+    while addr < end as u32 {
+        let mut instruction = decode(source, addr as u16);
+        // an instruction running past the end keeps only the bytes inside the range
+        instruction.bytes.truncate((end as u32 - addr) as usize);
+        addr += instruction.bytes.len() as u32;
+        decoded.push(instruction);
+    }
+    decoded
+}
+
+// RGBDS listing of start..end with labels for every target inside that range
+pub fn disassemble(source: &impl CodeSource, start: u16, end: u16) -> String {
+    render(&[(None, sweep(source, start, end))])
+}
+
+// RGBDS listing of whole ROM banks, one SECTION each, optionally limited to
+// start..end within every bank. labels resolve across all the listed banks
+pub fn disassemble_rom(rom: &[u8], banks: RangeInclusive<u16>, start: u16, end: u16) -> String {
+    let sections: Vec<(Option<String>, Vec<Decoded>)> = banks
+        .map(|bank| {
+            let (window, section) = if bank == 0 {
+                (0x0000..0x4000, "ROM0[$0000]".to_string())
+            } else {
+                (0x4000..0x8000, format!("ROMX[$4000], BANK[${:02X}]", bank))
+            };
+            let from = start.clamp(window.start, window.end);
+            let to = end.clamp(from, window.end);
+
+            let header = format!("SECTION \"ROM Bank ${:02X}\", {}", bank, section);
+            (Some(header), sweep(&RomBank::new(rom, bank), from, to))
+        })
+        .collect();
+    render(&sections)
+}
+
+fn render(sections: &[(Option<String>, Vec<Decoded>)]) -> String {
+    let decoded = || sections.iter().flat_map(|(_, decoded)| decoded);
+
+    let starts: BTreeSet<(u16, u16)> = decoded().map(|d| (d.bank, d.addr)).collect();
+    let labels: BTreeSet<(u16, u16)> = decoded()
+        .filter_map(|d| d.target)
+        .filter(|key| starts.contains(key))
+        .collect();
+    let label = |bank: u16, addr: u16| {
+        labels
+            .contains(&(bank, addr))
+            .then(|| label_name(bank, addr))
+    };
+
+    let mut out = String::new();
+    for (header, decoded) in sections {
+        if let Some(header) = header {
+            if !out.is_empty() {
+                out.push('\n');
+            }
+            let _ = writeln!(out, "{}\n", header);
+        }
+        for d in decoded {
+            if labels.contains(&(d.bank, d.addr)) {
+                let _ = writeln!(out, "{}:", label_name(d.bank, d.addr));
+            }
+            let text = if d.is_truncated() {
+                let data: Vec<String> = d.bytes.iter().map(|b| format!("${:02X}", b)).collect();
+                format!("db {}", data.join(", "))
+            } else {
+                d.format(label)
+            };
+            let bytes: Vec<String> = d.bytes.iter().map(|b| format!("{:02X}", b)).collect();
+            let _ = writeln!(
+                out,
+                "    {:<24}; {:02X}:{:04X} {}",
+                text,
+                d.bank,
+                d.addr,
+                bytes.join(" ")
+            );
+        }
+    }
+    out
+}
+
+pub fn label_name(bank: u16, addr: u16) -> String {
+    format!("L{:02X}_{:04X}", bank, addr)
+}
@@ -0,0 +1,80 @@
+use std::{fmt, io, path::PathBuf};
+
+use crate::cartridge::MapperKind;
+
+pub type Result<T> = std::result::Result<T, Error>;
+
+#[derive(Debug)]
+pub enum Error {
+    // reading or writing a ROM, boot ROM or save file failed
+    Io { path: PathBuf, source: io::Error },
+    // every cartridge has at least the two 16 KiB banks at 0x0000 - 0x7FFF
+    RomTooSmall(usize),
+    // larger than the 512 banks MBC5 can address
+    RomTooLarge(usize),
+    // the boot ROM refuses to start a cartridge whose header checksum doesn't match;
+    // without one loaded, Device::strict_header makes this an error instead of a warning
+    BadHeaderChecksum { expected: u8, actual: u8 },
+    // the header names a mapper that isn't emulated; Device::allow_unsupported_mapper
+    // runs it as ROM only instead
+    UnsupportedMapper { code: u8, mapper: MapperKind },
+    // boot ROMs are 256 bytes (DMG) or 2304 bytes (CGB)
+    BootRomSize(usize),
+}
+
+impl Error {
+    pub fn io(path: impl Into<PathBuf>, source: io::Error) -> Error {
+        Error::Io {
+            path: path.into(),
+            source,
+        }
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Error::Io { path, source } => write!(f, "{}: {}", path.display(), source),
+            Error::RomTooSmall(size) => {
+                write!(
+                    f,
+                    "ROM is too small ({} bytes, at least 32 KiB expected)",
+                    size
+                )
+            }
+            Error::RomTooLarge(size) => {
+                write!(
+                    f,
+                    "ROM is too large ({} bytes, at most 8 MiB supported)",
+                    size
+                )
+            }
+            Error::BadHeaderChecksum { expected, actual } => write!(
+                f,
+                "cartridge header checksum mismatch (header says 0x{:02X}, computed 0x{:02X})",
+                expected, actual
+            ),
+            Error::UnsupportedMapper { code, mapper } => {
+                write!(
+                    f,
+                    "unsupported cartridge type 0x{:02X} ({:?})",
+                    code, mapper
+                )
+            }
+            Error::BootRomSize(size) => write!(
+                f,
+                "boot ROM is {} bytes, expected 256 (DMG) or 2304 (CGB)",
+                size
+            ),
+        }
+    }
+}
+
+impl std::error::Error for Error {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Error::Io { source, .. } => Some(source),
+            _ => None,
+        }
+    }
+}
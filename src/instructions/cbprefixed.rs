@@ -14,8 +14,6 @@ impl CPU {
 
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rl_8(&mut self, reg: Register) {
@@ -32,31 +30,27 @@ impl CPU {
 
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rlc_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 7) == 1);
 
         let result = value.rotate_left(1);
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
 
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn rl_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let carry = self.registers.get_c_flag() as u8;
 
@@ -64,14 +58,12 @@ impl CPU {
             .set_c_flag(CPU::get_bit_at_position(value, 7) == 1);
 
         let result = (value << 1) | carry;
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
 
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn rrc_8(&mut self, reg: Register) {
@@ -86,8 +78,6 @@ impl CPU {
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rr_8(&mut self, reg: Register) {
@@ -103,43 +93,37 @@ impl CPU {
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rrc_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 0) == 1);
 
         let result = value.rotate_right(1);
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn rr_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
         let carry = self.registers.get_c_flag() as u8;
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 0) == 1);
 
         let result = (value >> 1) | (carry << 7);
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn sla_8(&mut self, reg: Register) {
@@ -154,25 +138,21 @@ impl CPU {
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn sla_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 7) == 1);
 
         let result = value << 1;
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn sra_8(&mut self, reg: Register) {
@@ -187,27 +167,24 @@ impl CPU {
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn sra_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 7) == 1);
 
         let result = (value >> 1) | (value & 0x80);
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(12);
     }
 
+    #[allow(clippy::manual_rotate)]
     pub fn swap_8(&mut self, reg: Register) {
         let value = self.registers.get_register8(reg);
 
@@ -219,24 +196,21 @@ impl CPU {
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
         self.registers.set_c_flag(false);
-
-        self.handle_cycles(4);
     }
 
+    #[allow(clippy::manual_rotate)]
     pub fn swap_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = (value >> 4) | (value << 4);
 
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
         self.registers.set_c_flag(false);
-
-        self.handle_cycles(12);
     }
 
     pub fn srl_8(&mut self, reg: Register) {
@@ -252,26 +226,22 @@ impl CPU {
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(8);
     }
 
     pub fn srl_mem16(&mut self, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers
             .set_c_flag(CPU::get_bit_at_position(value, 0) == 1);
 
         let result = value >> 1;
 
-        self.write_byte(addr, result);
+        self.write_cycle(addr, result);
 
         self.update_z_flag(result);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(16);
     }
 
     pub fn bit_8(&mut self, bit: u8, reg: Register) {
@@ -281,21 +251,17 @@ impl CPU {
         self.update_z_flag(bit_set);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(true);
-
-        self.handle_cycles(4);
     }
 
     pub fn bit_mem16(&mut self, bit: u8, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let bit_set = CPU::get_bit_at_position(value, bit);
 
         self.update_z_flag(bit_set);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(true);
-
-        self.handle_cycles(12);
     }
 
     pub fn res_8(&mut self, bit: u8, reg: Register) {
@@ -304,17 +270,15 @@ impl CPU {
         let result = value & !(1 << bit);
 
         self.registers.set_register8(reg, result);
-        self.handle_cycles(4);
     }
 
     pub fn res_mem16(&mut self, bit: u8, reg: Register) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = value & !(1 << bit);
 
-        self.write_byte(addr, result);
-        self.handle_cycles(12);
+        self.write_cycle(addr, result);
     }
 
     pub fn set_8(&mut self, reg: Register, bit: u8) {
@@ -323,18 +287,14 @@ impl CPU {
         let result = value | (1 << bit);
 
         self.registers.set_register8(reg, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn set_mem16(&mut self, reg: Register, bit: u8) {
         let addr = self.registers.get_register16(reg);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = value | (1 << bit);
 
-        self.write_byte(addr, result);
-
-        self.handle_cycles(12);
+        self.write_cycle(addr, result);
     }
 }
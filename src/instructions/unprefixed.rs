@@ -1,53 +1,65 @@
 use crate::cpu::CPU;
 use crate::registers::Register;
+use crate::timer::Timer;
 
 impl CPU {
-    pub fn nop(&mut self) {
-        self.handle_cycles(4);
+    pub fn nop(&mut self) {}
+
+    // the 11 unused opcodes hang the CPU until reset; interrupts are no longer serviced
+    pub fn illegal(&mut self, opcode: u8) {
+        self.registers.pc = self.registers.pc.wrapping_sub(1);
+        self.locked = true;
+
+        self.bus.illegal_opcode(opcode, self.registers.pc);
     }
 
     pub fn stop(&mut self) {
+        // STOP is followed by a padding byte, normally 0x00, that is skipped
+        self.registers.pc = self.registers.pc.wrapping_add(1);
+        self.write_byte(Timer::DIV_ADDR, 0);
+
+        // with KEY1 armed, STOP switches CPU speed instead of stopping
+        if self.bus.switch_speed() {
+            return;
+        }
+
+        // the clock stops until a button press pulls a joypad line low, with the LCD blank
         self.stopped = true;
-        self.handle_cycles(4);
+        self.bus.stop();
     }
 
     pub fn ld_8_8(&mut self, dest: Register, src: Register) {
         let value = self.registers.get_register8(src);
         self.registers.set_register8(dest, value);
-        self.handle_cycles(4);
     }
 
     pub fn ld_8_imm1(&mut self, dest: Register) {
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
         self.registers.set_register8(dest, next_byte);
-        self.handle_cycles(8);
     }
 
     pub fn ld_16_imm2(&mut self, dest: Register) {
-        let next_byte1 = self.read_byte(self.registers.pc);
+        let next_byte1 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
-        let next_byte2 = self.read_byte(self.registers.pc);
+        let next_byte2 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let word = ((next_byte2 as u16) << 8) | (next_byte1 as u16);
 
         self.registers.set_register16(dest, word);
-        self.handle_cycles(12);
     }
 
     pub fn ld_mem16_8(&mut self, reg16: Register, src: Register) {
         let addr = self.registers.get_register16(reg16);
         let value = self.registers.get_register8(src);
-        self.write_byte(addr, value);
-        self.handle_cycles(8);
+        self.write_cycle(addr, value);
     }
 
     pub fn ld_8_mem16(&mut self, dest: Register, reg16: Register) {
         let addr = self.registers.get_register16(reg16);
-        let byte = self.read_byte(addr);
+        let byte = self.read_cycle(addr);
         self.registers.set_register8(dest, byte);
-        self.handle_cycles(8);
     }
 
     pub fn ld_mem16_8_inc_dec(&mut self, reg16: Register, src: Register, increment: bool) {
@@ -75,73 +87,67 @@ impl CPU {
     }
 
     pub fn ld_memimm2_sp(&mut self) {
-        let next_byte1 = self.read_byte(self.registers.pc);
+        let next_byte1 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
-        let next_byte2 = self.read_byte(self.registers.pc);
+        let next_byte2 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let word = ((next_byte2 as u16) << 8) | (next_byte1 as u16);
 
         let sp = self.registers.sp;
 
-        self.write_byte(word, (sp & 0x00FF) as u8);
-        self.write_byte(word.wrapping_add(1), (sp >> 8) as u8);
-
-        self.handle_cycles(20);
+        self.write_cycle(word, (sp & 0x00FF) as u8);
+        self.write_cycle(word.wrapping_add(1), (sp >> 8) as u8);
     }
 
     pub fn ld_mem8_8(&mut self, reg8: Register, src: Register) {
         let offset = self.registers.get_register8(reg8) as u16;
         let addr = 0xFF00 + offset;
         let value = self.registers.get_register8(src);
-        self.write_byte(addr, value);
-        self.handle_cycles(8);
+        self.write_cycle(addr, value);
     }
 
     pub fn ld_8_mem8(&mut self, dest: Register, reg8: Register) {
         let offset = self.registers.get_register8(reg8) as u16;
         let addr = 0xFF00 + offset;
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
         self.registers.set_register8(dest, value);
-        self.handle_cycles(8);
     }
 
     pub fn ld_memimm2_8(&mut self, src: Register) {
-        let next_byte1 = self.read_byte(self.registers.pc);
+        let next_byte1 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
-        let next_byte2 = self.read_byte(self.registers.pc);
+        let next_byte2 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let addr = ((next_byte2 as u16) << 8) | (next_byte1 as u16);
 
         let value = self.registers.get_register8(src);
 
-        self.write_byte(addr, value);
-        self.handle_cycles(16);
+        self.write_cycle(addr, value);
     }
 
     pub fn ld_8_memimm2(&mut self, dest: Register) {
-        let next_byte1 = self.read_byte(self.registers.pc);
+        let next_byte1 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
-        let next_byte2 = self.read_byte(self.registers.pc);
+        let next_byte2 = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let addr = ((next_byte2 as u16) << 8) | (next_byte1 as u16);
 
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         self.registers.set_register8(dest, value);
-        self.handle_cycles(16);
     }
 
     pub fn ld_16_16(&mut self, dest: Register, src: Register) {
         let value = self.registers.get_register16(src);
         self.registers.set_register16(dest, value);
-        self.handle_cycles(8);
+        self.handle_cycles(4);
     }
 
     pub fn ld_hl_sp_r8(&mut self) {
-        let r8 = self.read_byte(self.registers.pc) as i8;
+        let r8 = self.read_cycle(self.registers.pc) as i8;
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let sp = self.registers.sp;
@@ -154,45 +160,39 @@ impl CPU {
         self.update_h_flag8(sp as u8, r8 as u8, false);
         self.update_c_flag8(sp as u8, r8 as u8, false);
 
-        self.handle_cycles(12);
+        self.handle_cycles(4);
     }
 
     pub fn ldh_memimm1_8(&mut self, src: Register) {
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let addr = 0xFF00 + next_byte as u16;
         let value = self.registers.get_register8(src);
-        self.write_byte(addr, value);
-
-        self.handle_cycles(12);
+        self.write_cycle(addr, value);
     }
 
     pub fn ldh_8_memimm1(&mut self, dest: Register) {
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let addr = 0xFF00 + next_byte as u16;
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
         self.registers.set_register8(dest, value);
-
-        self.handle_cycles(12);
     }
 
     pub fn ld_mem16_imm1(&mut self, reg16: Register) {
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let addr = self.registers.get_register16(reg16);
-        self.write_byte(addr, next_byte);
-
-        self.handle_cycles(12);
+        self.write_cycle(addr, next_byte);
     }
 
     pub fn inc_16(&mut self, reg16: Register) {
         self.registers
             .set_register16(reg16, self.registers.get_register16(reg16).wrapping_add(1));
-        self.handle_cycles(8);
+        self.handle_cycles(4);
     }
 
     pub fn inc_8(&mut self, reg8: Register) {
@@ -204,14 +204,24 @@ impl CPU {
         self.update_h_flag8(reg8_value, 1, false); // Set H flag for addition
 
         self.registers.set_register8(reg8, result);
+    }
 
-        self.handle_cycles(4);
+    pub fn inc_mem16(&mut self, reg16: Register) {
+        let addr = self.registers.get_register16(reg16);
+        let value = self.read_cycle(addr);
+        let result = value.wrapping_add(1);
+
+        self.update_z_flag(result);
+        self.registers.set_s_flag(false);
+        self.update_h_flag8(value, 1, false);
+
+        self.write_cycle(addr, result);
     }
 
     pub fn dec_16(&mut self, reg16: Register) {
         self.registers
             .set_register16(reg16, self.registers.get_register16(reg16).wrapping_sub(1));
-        self.handle_cycles(8);
+        self.handle_cycles(4);
     }
 
     pub fn dec_8(&mut self, reg8: Register) {
@@ -223,7 +233,18 @@ impl CPU {
         self.update_h_flag8(reg8_value, 1, true);
 
         self.registers.set_register8(reg8, result);
-        self.handle_cycles(4);
+    }
+
+    pub fn dec_mem16(&mut self, reg16: Register) {
+        let addr = self.registers.get_register16(reg16);
+        let value = self.read_cycle(addr);
+        let result = value.wrapping_sub(1);
+
+        self.update_z_flag(result);
+        self.registers.set_s_flag(true);
+        self.update_h_flag8(value, 1, true);
+
+        self.write_cycle(addr, result);
     }
 
     pub fn rlca(&mut self) {
@@ -237,8 +258,6 @@ impl CPU {
         self.registers.set_z_flag(false);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rrca(&mut self) {
@@ -252,51 +271,38 @@ impl CPU {
         self.registers.set_z_flag(false);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn jr_nz_imm1(&mut self) {
-        if !self.registers.get_z_flag() {
-            self.jr_imm1();
-        } else {
-            self.handle_cycles(8);
-        }
+        self.jr_if(!self.registers.get_z_flag());
     }
 
     pub fn jr_nc_imm1(&mut self) {
-        if !self.registers.get_c_flag() {
-            self.jr_imm1();
-        } else {
-            self.handle_cycles(8);
-        }
+        self.jr_if(!self.registers.get_c_flag());
     }
 
     pub fn jr_z_imm1(&mut self) {
-        if self.registers.get_z_flag() {
-            self.jr_imm1();
-        } else {
-            self.handle_cycles(8);
-        }
+        self.jr_if(self.registers.get_z_flag());
     }
 
     pub fn jr_c_imm1(&mut self) {
-        if self.registers.get_c_flag() {
-            self.jr_imm1();
-        } else {
-            self.handle_cycles(8);
-        }
+        self.jr_if(self.registers.get_c_flag());
     }
 
     pub fn jr_imm1(&mut self) {
-        let offset = self.read_byte(self.registers.pc) as i8;
-        self.registers.pc = self.registers.pc.wrapping_add(1);
+        self.jr_if(true);
+    }
 
-        let pc = self.registers.pc as i16;
-        let new_pc = pc.wrapping_add(offset as i16) as u16;
-        self.registers.pc = new_pc;
+    // the offset is read either way; taking the jump costs one more M-cycle
+    fn jr_if(&mut self, condition: bool) {
+        let offset = self.read_cycle(self.registers.pc) as i8;
+        self.registers.pc = self.registers.pc.wrapping_add(1);
 
-        self.handle_cycles(12);
+        if condition {
+            let pc = self.registers.pc as i16;
+            self.registers.pc = pc.wrapping_add(offset as i16) as u16;
+            self.handle_cycles(4);
+        }
     }
 
     pub fn rla(&mut self) {
@@ -311,8 +317,6 @@ impl CPU {
         self.registers.set_z_flag(false);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn rra(&mut self) {
@@ -327,19 +331,17 @@ impl CPU {
         self.registers.set_z_flag(false);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn daa(&mut self) {
         let mut adjust = 0;
-        let mut carry = false;
+        let mut carry = self.registers.get_c_flag();
 
         if !self.registers.get_s_flag() {
             if self.registers.get_h_flag() || (self.registers.a & 0x0F) > 9 {
                 adjust |= 0x06;
             }
-            if self.registers.get_c_flag() || (self.registers.a & 0xF0) > 0x90 {
+            if self.registers.get_c_flag() || self.registers.a > 0x99 {
                 adjust |= 0x60;
                 carry = true;
             }
@@ -359,26 +361,29 @@ impl CPU {
 
         self.update_z_flag(self.registers.a);
         self.registers.set_h_flag(false);
-
-        if carry {
-            self.registers.set_c_flag(carry);
-        }
-
-        self.handle_cycles(4);
+        self.registers.set_c_flag(carry);
     }
 
     pub fn scf(&mut self) {
         self.registers.set_c_flag(true);
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn halt(&mut self) {
-        self.halted = true;
-
-        self.handle_cycles(4);
+        // with IME clear and an interrupt already pending HALT doesn't halt at all,
+        // and the byte after it gets fetched twice
+        if !self.ime && self.interrupt_pending() {
+            if self.ime_pending {
+                // right after EI the interrupt is taken with HALT's own address pushed,
+                // so HALT runs again once the handler returns
+                self.registers.pc = self.registers.pc.wrapping_sub(1);
+            } else {
+                self.halt_bug = true;
+            }
+        } else {
+            self.halted = true;
+        }
     }
 
     pub fn cpl(&mut self) {
@@ -386,8 +391,6 @@ impl CPU {
 
         self.registers.set_s_flag(true);
         self.registers.set_h_flag(true);
-
-        self.handle_cycles(4);
     }
 
     pub fn ccf(&mut self) {
@@ -395,8 +398,6 @@ impl CPU {
 
         self.registers.set_s_flag(false);
         self.registers.set_h_flag(false);
-
-        self.handle_cycles(4);
     }
 
     pub fn add_16_16(&mut self, dest: Register, src: Register) {
@@ -410,7 +411,7 @@ impl CPU {
 
         self.registers.set_register16(dest, result);
 
-        self.handle_cycles(8);
+        self.handle_cycles(4);
     }
 
     pub fn add_8_8(&mut self, dest: Register, src: Register) {
@@ -419,8 +420,6 @@ impl CPU {
         let result = self.add(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn sub_8_8(&mut self, dest: Register, src: Register) {
@@ -429,8 +428,6 @@ impl CPU {
         let result = self.sub(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn and_8_8(&mut self, dest: Register, src: Register) {
@@ -439,8 +436,6 @@ impl CPU {
         let result = self.and(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn or_8_8(&mut self, dest: Register, src: Register) {
@@ -449,104 +444,86 @@ impl CPU {
         let result = self.or(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn add_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.add(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn sub_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.sub(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn and_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.and(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn or_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.or(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn add_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.add(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn sub_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.sub(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn and_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.and(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn or_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.or(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn adc_8_8(&mut self, dest: Register, src: Register) {
@@ -556,8 +533,6 @@ impl CPU {
         let result = self.adc(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn sbc_8_8(&mut self, dest: Register, src: Register) {
@@ -567,8 +542,6 @@ impl CPU {
         let result = self.sbc(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
     pub fn xor_8_8(&mut self, dest: Register, src: Register) {
@@ -578,161 +551,133 @@ impl CPU {
         let result = self.xor(reg1, reg2);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(4);
     }
 
+    #[allow(unused_variables)]
     pub fn cp_8_8(&mut self, dest: Register, src: Register) {
         let reg1 = self.registers.get_register8(dest);
         let reg2 = self.registers.get_register8(src);
 
         let result = self.cp(reg1, reg2);
-
-        self.handle_cycles(4);
     }
 
     pub fn adc_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.adc(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn sbc_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.sbc(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn xor_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.xor(reg, value);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
+    #[allow(unused_variables)]
     pub fn cp_8_mem16(&mut self, dest: Register, reg16: Register) {
         let reg = self.registers.get_register8(dest);
         let addr = self.registers.get_register16(reg16);
-        let value = self.read_byte(addr);
+        let value = self.read_cycle(addr);
 
         let result = self.cp(reg, value);
-
-        self.handle_cycles(8);
     }
 
     pub fn adc_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.adc(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn sbc_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.sbc(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
     pub fn xor_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.xor(reg, next_byte);
 
         self.registers.set_register8(dest, result);
-
-        self.handle_cycles(8);
     }
 
+    #[allow(unused_variables)]
     pub fn cp_8_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register8(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let next_byte = self.read_cycle(self.registers.pc);
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
         let result = self.cp(reg, next_byte);
-
-        self.handle_cycles(8);
     }
 
     pub fn add_16_imm1(&mut self, dest: Register) {
         let reg = self.registers.get_register16(dest);
-        let next_byte = self.read_byte(self.registers.pc);
+        let r8 = self.read_cycle(self.registers.pc) as i8;
         self.registers.pc = self.registers.pc.wrapping_add(1);
 
-        let result = reg.wrapping_add(next_byte as u16);
+        let result = reg.wrapping_add(r8 as u16);
 
+        // like LD HL, SP+e8 the flags come from the unsigned low byte addition
         self.registers.set_z_flag(false);
         self.registers.set_s_flag(false);
-        self.update_h_flag16(reg, next_byte as u16, false);
-        self.update_c_flag16(reg, next_byte as u16, false);
+        self.update_h_flag8(reg as u8, r8 as u8, false);
+        self.update_c_flag8(reg as u8, r8 as u8, false);
 
         self.registers.set_register16(dest, result);
 
-        self.handle_cycles(16);
+        self.handle_cycles(8);
     }
 
     pub fn ret_nz(&mut self) {
-        if !self.registers.get_z_flag() {
-            self.ret();
-            self.handle_cycles(4);
-        } else {
-            self.handle_cycles(8);
-        }
+        self.ret_if(!self.registers.get_z_flag());
     }
 
     pub fn ret_nc(&mut self) {
-        if !self.registers.get_c_flag() {
-            self.ret();
-            self.handle_cycles(4);
-        } else {
-            self.handle_cycles(8);
-        }
+        self.ret_if(!self.registers.get_c_flag());
     }
 
     pub fn ret_z(&mut self) {
-        if self.registers.get_z_flag() {
-            self.ret();
-            self.handle_cycles(4);
-        } else {
-            self.handle_cycles(8);
-        }
+        self.ret_if(self.registers.get_z_flag());
     }
 
     pub fn ret_c(&mut self) {
-        if self.registers.get_c_flag() {
+        self.ret_if(self.registers.get_c_flag());
+    }
+
+    // conditional returns spend an extra M-cycle checking the condition
+    fn ret_if(&mut self, condition: bool) {
+        self.handle_cycles(4);
+        if condition {
             self.ret();
-            self.handle_cycles(4);
-        } else {
-            self.handle_cycles(8);
         }
     }
 
@@ -740,152 +685,116 @@ impl CPU {
         let addr = self.pop_stack();
 
         self.registers.pc = addr;
-        self.handle_cycles(16);
+        self.handle_cycles(4);
     }
 
     pub fn pop_16(&mut self, reg16: Register) {
         let word = self.pop_stack();
         self.registers.set_register16(reg16, word);
-
-        self.handle_cycles(12);
     }
 
     pub fn push_16(&mut self, reg16: Register) {
         let value = self.registers.get_register16(reg16);
 
+        // SP is decremented in an internal M-cycle before the two writes
+        self.handle_cycles(4);
         self.push_stack(value);
-
-        self.handle_cycles(16);
     }
 
     pub fn jp_nz_imm2(&mut self) {
-        if !self.registers.get_z_flag() {
-            self.jp_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.jp_if(!self.registers.get_z_flag());
     }
 
     pub fn jp_nc_imm2(&mut self) {
-        if !self.registers.get_c_flag() {
-            self.jp_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.jp_if(!self.registers.get_c_flag());
     }
 
     pub fn jp_z_imm2(&mut self) {
-        if self.registers.get_z_flag() {
-            self.jp_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.jp_if(self.registers.get_z_flag());
     }
 
     pub fn jp_c_imm2(&mut self) {
-        if self.registers.get_c_flag() {
-            self.jp_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.jp_if(self.registers.get_c_flag());
     }
 
     pub fn jp_imm2(&mut self) {
-        let next_byte1 = self.read_byte(self.registers.pc);
-        self.registers.pc = self.registers.pc.wrapping_add(1);
-        let next_byte2 = self.read_byte(self.registers.pc);
-        self.registers.pc = self.registers.pc.wrapping_add(1);
+        self.jp_if(true);
+    }
 
-        let addr = ((next_byte2 as u16) << 8) | (next_byte1 as u16);
+    fn jp_if(&mut self, condition: bool) {
+        let addr = self.read_imm16();
 
-        self.registers.pc = addr;
-        self.handle_cycles(16);
+        if condition {
+            self.registers.pc = addr;
+            self.handle_cycles(4);
+        }
     }
 
     pub fn jp_mem16(&mut self, dest: Register) {
         let addr = self.registers.get_register16(dest);
 
         self.registers.pc = addr;
-        self.handle_cycles(4);
     }
 
     pub fn call_nz_imm2(&mut self) {
-        if !self.registers.get_z_flag() {
-            self.call_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.call_if(!self.registers.get_z_flag());
     }
 
     pub fn call_nc_imm2(&mut self) {
-        if !self.registers.get_c_flag() {
-            self.call_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.call_if(!self.registers.get_c_flag());
     }
 
     pub fn call_z_imm2(&mut self) {
-        if self.registers.get_z_flag() {
-            self.call_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.call_if(self.registers.get_z_flag());
     }
 
     pub fn call_c_imm2(&mut self) {
-        if self.registers.get_c_flag() {
-            self.call_imm2();
-        } else {
-            self.handle_cycles(12);
-        }
+        self.call_if(self.registers.get_c_flag());
     }
 
     pub fn call_imm2(&mut self) {
-        let low_byte = self.read_byte(self.registers.pc);
-        self.registers.pc = self.registers.pc.wrapping_add(1);
-        let high_byte = self.read_byte(self.registers.pc);
-        self.registers.pc = self.registers.pc.wrapping_add(1);
+        self.call_if(true);
+    }
 
-        let addr = ((high_byte as u16) << 8) | (low_byte as u16);
+    fn call_if(&mut self, condition: bool) {
+        let addr = self.read_imm16();
 
-        let pc = self.registers.pc;
-        self.push_stack(pc);
-
-        self.registers.pc = addr;
+        if condition {
+            let pc = self.registers.pc;
+            self.handle_cycles(4);
+            self.push_stack(pc);
 
-        self.handle_cycles(24);
+            self.registers.pc = addr;
+        }
     }
 
     pub fn reti(&mut self) {
         self.ret();
+        // unlike EI, RETI enables interrupts without a delay
         self.ime = true;
     }
 
     pub fn rst(&mut self, target: u16) {
         let pc = self.registers.pc;
+        self.handle_cycles(4);
         self.push_stack(pc);
 
         self.registers.pc = target;
-
-        self.handle_cycles(16);
     }
 
     pub fn di(&mut self) {
         self.ime = false;
-        self.handle_cycles(4);
+        self.ime_pending = false;
     }
 
+    // IME only turns on after the instruction following EI
     pub fn ei(&mut self) {
-        self.ime = true;
-        self.handle_cycles(4);
+        self.ime_pending = true;
     }
 
     pub fn prefix_cb(&mut self) {
         let opcode = self.fetch_opcode();
 
         self.execute_cb_opcode(opcode);
-
-        self.handle_cycles(4);
     }
 }
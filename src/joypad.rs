@@ -0,0 +1,154 @@
+/*
+0xFF00: P1/JOYP
+Bit 5: select action buttons (0 = selected)
+Bit 4: select direction buttons (0 = selected)
+Bit 3: Down or Start (0 = pressed)
+Bit 2: Up or Select (0 = pressed)
+Bit 1: Left or B (0 = pressed)
+Bit 0: Right or A (0 = pressed)
+*/
+
+#[derive(Clone, Copy, Default, PartialEq, Eq)]
+pub struct Buttons {
+    pub right: bool,
+    pub left: bool,
+    pub up: bool,
+    pub down: bool,
+    pub a: bool,
+    pub b: bool,
+    pub select: bool,
+    pub start: bool,
+}
+
+pub struct Joypad {
+    pub select: u8, // bits 4-5 as last written
+    pub buttons: Buttons,
+}
+
+impl Joypad {
+    pub fn new() -> Joypad {
+        Joypad {
+            select: 0x30,
+            buttons: Buttons::default(),
+        }
+    }
+
+    // active-low state of the four input lines for the current selection
+    fn lines(&self) -> u8 {
+        let mut pressed = 0;
+
+        if self.select & 0x10 == 0 {
+            pressed |= (self.buttons.right as u8)
+                | (self.buttons.left as u8) << 1
+                | (self.buttons.up as u8) << 2
+                | (self.buttons.down as u8) << 3;
+        }
+        if self.select & 0x20 == 0 {
+            pressed |= (self.buttons.a as u8)
+                | (self.buttons.b as u8) << 1
+                | (self.buttons.select as u8) << 2
+                | (self.buttons.start as u8) << 3;
+        }
+
+        !pressed & 0x0F
+    }
+
+    pub fn read_byte(&self) -> u8 {
+        0xC0 | self.select | self.lines()
+    }
+
+    // stores the select bits; the lines are re-read with the new selection, so
+    // selecting a group with a button already held also counts as a falling edge
+    pub fn write_byte(&mut self, value: u8) -> bool {
+        let old_lines = self.lines();
+        self.select = value & 0x30;
+        old_lines & !self.lines() != 0
+    }
+
+    // returns true if a line went from high to low, which requests the joypad interrupt
+    pub fn set_buttons(&mut self, buttons: Buttons) -> bool {
+        let old_lines = self.lines();
+        self.buttons = buttons;
+        old_lines & !self.lines() != 0
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Buttons, Joypad};
+    use crate::memory::Memory;
+
+    #[test]
+    fn select_bits_pick_the_button_group() {
+        let mut joypad = Joypad::new();
+        joypad.set_buttons(Buttons {
+            right: true,
+            down: true,
+            a: true,
+            start: true,
+            ..Buttons::default()
+        });
+
+        // nothing selected: all lines read high
+        assert_eq!(joypad.read_byte(), 0xFF);
+
+        joypad.write_byte(0x20);
+        assert_eq!(joypad.read_byte(), 0xE0 | 0x06);
+
+        joypad.write_byte(0x10);
+        assert_eq!(joypad.read_byte(), 0xD0 | 0x06);
+
+        // both groups selected: the lines are ANDed together
+        joypad.set_buttons(Buttons {
+            left: true,
+            select: true,
+            ..Buttons::default()
+        });
+        joypad.write_byte(0x00);
+        assert_eq!(joypad.read_byte(), 0xC0 | 0x09);
+    }
+
+    #[test]
+    fn unused_bits_read_as_one() {
+        let mut joypad = Joypad::new();
+        for value in [0x00, 0x10, 0x20, 0x30, 0xFF] {
+            joypad.write_byte(value);
+            assert_eq!(joypad.read_byte() & 0xC0, 0xC0);
+            assert_eq!(joypad.read_byte() & 0x30, value & 0x30);
+        }
+    }
+
+    #[test]
+    fn interrupt_only_on_a_falling_edge() {
+        let mut memory = Memory::new();
+        memory.write_byte(0xFF0F, 0x00);
+        memory.write_byte(0xFF00, 0x10);
+
+        // direction pressed while only the action group is selected
+        memory.set_buttons(Buttons {
+            up: true,
+            ..Buttons::default()
+        });
+        assert_eq!(memory.read_byte(0xFF0F) & 0x10, 0);
+
+        let a = Buttons {
+            a: true,
+            ..Buttons::default()
+        };
+        memory.set_buttons(a);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x10, 0x10);
+
+        // holding the button or releasing it doesn't raise it again
+        memory.write_byte(0xFF0F, 0x00);
+        memory.set_buttons(a);
+        memory.set_buttons(Buttons::default());
+        assert_eq!(memory.read_byte(0xFF0F) & 0x10, 0);
+
+        // selecting the group of a held button pulls its line low
+        memory.write_byte(0xFF00, 0x30);
+        memory.set_buttons(a);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x10, 0);
+        memory.write_byte(0xFF00, 0x10);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x10, 0x10);
+    }
+}
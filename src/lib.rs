@@ -0,0 +1,44 @@
+//! Game Boy (LR35902) emulator core. `Device` wires the CPU, PPU, APU and cartridge
+//! together and has no windowing or host audio dependencies; the `lr35902-emulator`
+//! binary is a minifb frontend on top of it.
+
+#![allow(clippy::upper_case_acronyms, clippy::new_without_default)]
+
+#[path = "instructions/cbprefixed.rs"]
+mod cbprefixed;
+#[path = "instructions/unprefixed.rs"]
+mod unprefixed;
+
+#[cfg(test)]
+mod mbc;
+#[path = "mbc/mbc1.rs"]
+pub mod mbc1;
+#[path = "mbc/mbc2.rs"]
+pub mod mbc2;
+#[path = "mbc/mbc3.rs"]
+pub mod mbc3;
+#[path = "mbc/mbc5.rs"]
+pub mod mbc5;
+
+pub mod apu;
+pub mod audio;
+pub mod bus;
+pub mod cartridge;
+pub mod cpu;
+pub mod device;
+pub mod disasm;
+pub mod error;
+pub mod joypad;
+pub mod memory;
+pub mod model;
+pub mod opcodes;
+pub mod ppu;
+pub mod registers;
+pub mod timer;
+pub mod trace;
+pub mod wav;
+
+pub use device::{Device, DeviceEvent};
+pub use error::{Error, Result};
+pub use joypad::Buttons;
+pub use model::Model;
@@ -1,64 +1,280 @@
-#[path = "instructions/cbprefixed.rs"]
-mod cbprefixed;
-#[path = "instructions/unprefixed.rs"]
-mod unprefixed;
+mod cli;
 
-mod cpu;
-mod device;
-mod memory;
-mod ppu;
-mod registers;
-
-use device::Device;
+use cli::{Command, DisasmOptions, Options};
+use lr35902_emulator::{
+    apu,
+    audio::{AudioOutput, AudioSink, CommandSink, NullSink, HOST_SAMPLE_RATE},
+    disasm::{self, RomBank},
+    ppu::{SCREEN_HEIGHT, SCREEN_WIDTH},
+    Buttons, Device, DeviceEvent, Error,
+};
 
 use std::env;
-use std::path::PathBuf;
+use std::fs;
+use std::path::Path;
+use std::process;
+use std::thread;
+use std::time::Duration;
+
+use minifb::{Key, KeyRepeat, Window, WindowOptions};
+
+const WIDTH: usize = SCREEN_WIDTH;
+const HEIGHT: usize = SCREEN_HEIGHT;
 
-use minifb::{Key, Window, WindowOptions};
+const TITLE: &str = "Game Boy Emulator";
 
-const PATH_TO_ROM: &str = "roms/wordzap.gb";
+// F9 toggles recording the audio mix, Shift+F9 also records each channel
+const RECORDING_PATH: &str = "recording.wav";
 
-const WIDTH: usize = 160;
-const HEIGHT: usize = 144;
-const SCALE: usize = 4;
+// flush battery-backed RAM to disk about every 5 seconds of emulated time
+const AUTOSAVE_FRAMES: u32 = 300;
 
 fn main() {
-    let mut gb = Device::new();
+    let options = match cli::parse(env::args().skip(1)) {
+        Ok(Command::Run(options)) => options,
+        Ok(Command::Disasm(options)) => {
+            if let Err(e) = run_disasm(&options) {
+                eprintln!("error: {}", e);
+                process::exit(1);
+            }
+            return;
+        }
+        Ok(Command::Help) => {
+            println!("{}", cli::USAGE);
+            return;
+        }
+        Err(e) => {
+            eprintln!("error: {}\n\n{}", e, cli::USAGE);
+            process::exit(2);
+        }
+    };
+
+    if let Err(e) = run(&options) {
+        eprintln!("error: {}", e);
+        process::exit(1);
+    }
+}
 
-    let manifest_dir = env::var("CARGO_MANIFEST_DIR").unwrap();
-    let rom_path = PathBuf::from(manifest_dir).join(PATH_TO_ROM);
+fn run(options: &Options) -> Result<(), String> {
+    let mut gb = Device::with_model(options.model);
+    gb.save_dir = options.save_dir.clone();
+    gb.rtc_clock = options.rtc_clock;
+    gb.breakpoints = options.breakpoints.clone();
+    gb.strict_header = options.strict_header;
+    gb.allow_unsupported_mapper = options.allow_unsupported_mapper;
+    if let Some(trace) = &options.trace {
+        gb.start_trace(trace, options.trace_filter.clone())
+            .map_err(|e| Error::io(trace, e).to_string())?;
+        gb.set_doctor_mode(options.trace_doctor);
+    }
+    if let Some(boot_rom) = &options.boot_rom {
+        gb.load_boot_rom(boot_rom).map_err(|e| e.to_string())?;
+    }
+    gb.load_instructions(&options.rom_path)
+        .map_err(|e| e.to_string())?;
 
-    gb.load_instructions(rom_path.to_str().unwrap());
+    let result = if options.headless {
+        run_headless(&mut gb, options);
+        Ok(())
+    } else {
+        run_window(&mut gb, options)
+    };
+
+    if let Err(e) = gb.stop_trace() {
+        eprintln!("warning: failed to finish the trace: {}", e);
+    }
+    if let Err(e) = gb.stop_recording() {
+        eprintln!("warning: failed to finish recording: {}", e);
+    }
+    if let Err(e) = gb.write_save() {
+        eprintln!("warning: failed to write save file: {}", e);
+    }
+
+    result
+}
 
+fn run_disasm(options: &DisasmOptions) -> Result<(), String> {
+    let rom =
+        fs::read(&options.rom_path).map_err(|e| Error::io(&options.rom_path, e).to_string())?;
+
+    let bank_count = RomBank::bank_count(&rom);
+    let banks = options.banks.clone().unwrap_or(0..=bank_count - 1);
+    if *banks.end() >= bank_count {
+        return Err(format!(
+            "bank {} is out of range, the ROM has {} banks",
+            banks.end(),
+            bank_count
+        ));
+    }
+
+    let listing = disasm::disassemble_rom(&rom, banks, options.start, options.end);
+    match &options.output {
+        Some(path) => fs::write(path, listing).map_err(|e| Error::io(path, e).to_string()),
+        None => {
+            print!("{}", listing);
+            Ok(())
+        }
+    }
+}
+
+// runs as fast as possible with no window or audio, until the frame limit or a breakpoint
+fn run_headless(gb: &mut Device, options: &Options) {
+    let mut autosave = Autosave::new();
+    let mut frames = 0;
+
+    while options.frames.is_none_or(|limit| frames < limit) {
+        gb.run_frame();
+        gb.take_audio_samples();
+        frames += 1;
+        autosave.frame(gb);
+
+        for event in gb.take_events() {
+            match event {
+                DeviceEvent::Breakpoint(pc) => {
+                    report_breakpoint(gb, pc);
+                    return;
+                }
+                // nothing will change from here on
+                DeviceEvent::IllegalOpcode { opcode, pc } => {
+                    report_illegal_opcode(opcode, pc);
+                    return;
+                }
+                DeviceEvent::Rumble(_) => (),
+                DeviceEvent::Warning(message) => eprintln!("warning: {}", message),
+            }
+        }
+    }
+}
+
+fn run_window(gb: &mut Device, options: &Options) -> Result<(), String> {
+    let scale = options.scale;
     let mut window = Window::new(
-        "Game Boy Emulator",
-        WIDTH * SCALE,
-        HEIGHT * SCALE,
+        TITLE,
+        WIDTH * scale,
+        HEIGHT * scale,
         WindowOptions::default(),
     )
-    .unwrap_or_else(|e| {
-        panic!("Failed to create window: {}", e);
-    });
+    .map_err(|e| format!("failed to create window: {}", e))?;
 
-    loop {
+    let sink: Box<dyn AudioSink> = match CommandSink::aplay(HOST_SAMPLE_RATE) {
+        Ok(sink) => Box::new(sink),
+        Err(e) => {
+            eprintln!("warning: audio output unavailable ({}), running silent", e);
+            Box::new(NullSink::new(HOST_SAMPLE_RATE))
+        }
+    };
+    let mut audio = AudioOutput::new(sink, apu::SAMPLE_RATE);
+
+    let mut autosave = Autosave::new();
+    let mut frames = 0;
+
+    while options.frames.is_none_or(|limit| frames < limit) {
         if !window.is_open() || window.is_key_down(Key::Escape) {
             break;
         }
 
-        gb.cpu.cycle();
+        // the audio sink sets the pace: only run a frame once it wants more samples
+        if !audio.needs_samples() {
+            window.update();
+            thread::sleep(Duration::from_millis(1));
+            continue;
+        }
 
-        if gb.ppu.borrow().frame_ready {
-            let scaled_buffer =
-                scale_framebuffer(&gb.ppu.borrow().framebuffer, WIDTH, HEIGHT, SCALE);
+        if window.is_key_pressed(Key::F9, KeyRepeat::No) {
+            toggle_recording(gb, window.is_key_down(Key::LeftShift));
+        }
 
-            window
-                .update_with_buffer(&scaled_buffer, WIDTH * SCALE, HEIGHT * SCALE)
-                .unwrap();
+        gb.set_buttons(read_buttons(&window));
+        let frame_ready = gb.run_frame();
+        audio.push(&gb.take_audio_samples());
+        frames += 1;
+        autosave.frame(gb);
+
+        if frame_ready {
+            let scaled_buffer = scale_framebuffer(&gb.framebuffer(), WIDTH, HEIGHT, scale);
 
-            gb.ppu.borrow_mut().frame_ready = false;
+            window
+                .update_with_buffer(&scaled_buffer, WIDTH * scale, HEIGHT * scale)
+                .map_err(|e| format!("failed to draw the frame: {}", e))?;
         } else {
             window.update();
         }
+
+        for event in gb.take_events() {
+            match event {
+                DeviceEvent::Rumble(true) => window.set_title(&format!("{} [rumble]", TITLE)),
+                DeviceEvent::Rumble(false) => window.set_title(TITLE),
+                DeviceEvent::Breakpoint(pc) => {
+                    report_breakpoint(gb, pc);
+                    return Ok(());
+                }
+                // keep the window open on the frozen frame, like the real thing
+                DeviceEvent::IllegalOpcode { opcode, pc } => report_illegal_opcode(opcode, pc),
+                DeviceEvent::Warning(message) => eprintln!("warning: {}", message),
+            }
+        }
+    }
+
+    Ok(())
+}
+
+struct Autosave {
+    frames_since_save: u32,
+}
+
+impl Autosave {
+    fn new() -> Autosave {
+        Autosave {
+            frames_since_save: 0,
+        }
+    }
+
+    fn frame(&mut self, gb: &mut Device) {
+        self.frames_since_save += 1;
+        if self.frames_since_save >= AUTOSAVE_FRAMES {
+            self.frames_since_save = 0;
+            if let Err(e) = gb.write_save() {
+                eprintln!("warning: failed to write save file: {}", e);
+            }
+        }
+    }
+}
+
+fn report_breakpoint(gb: &Device, pc: u16) {
+    let r = &gb.cpu.registers;
+    eprintln!(
+        "breakpoint at {:04X}: A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} IME:{}",
+        pc, r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l, r.sp, gb.cpu.ime as u8
+    );
+}
+
+fn report_illegal_opcode(opcode: u8, pc: u16) {
+    eprintln!("CPU hung on illegal opcode {:02X} at {:04X}", opcode, pc);
+}
+
+fn toggle_recording(gb: &mut Device, per_channel: bool) {
+    if gb.is_recording() {
+        match gb.stop_recording() {
+            Ok(()) => eprintln!("recording saved to {}", RECORDING_PATH),
+            Err(e) => eprintln!("warning: failed to finish recording: {}", e),
+        }
+    } else if let Err(e) = gb.start_recording(Path::new(RECORDING_PATH), per_channel) {
+        eprintln!("warning: failed to start recording: {}", e);
+    } else {
+        eprintln!("recording audio to {}", RECORDING_PATH);
+    }
+}
+
+fn read_buttons(window: &Window) -> Buttons {
+    Buttons {
+        right: window.is_key_down(Key::Right),
+        left: window.is_key_down(Key::Left),
+        up: window.is_key_down(Key::Up),
+        down: window.is_key_down(Key::Down),
+        a: window.is_key_down(Key::X),
+        b: window.is_key_down(Key::Z),
+        select: window.is_key_down(Key::Backspace) || window.is_key_down(Key::RightShift),
+        start: window.is_key_down(Key::Enter),
     }
 }
 
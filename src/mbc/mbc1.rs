@@ -0,0 +1,199 @@
+use crate::cartridge::{ram_bank_offset, read_rom_bank, MemoryBankController, ROM_BANK_SIZE};
+
+/*
+0x0000 - 0x1FFF: RAM enable (0x0A in the low nibble enables).
+0x2000 - 0x3FFF: ROM bank number, lower 5 bits (BANK1).
+0x4000 - 0x5FFF: RAM bank number or upper ROM bank bits (BANK2).
+0x6000 - 0x7FFF: Banking mode select.
+*/
+
+pub struct Mbc1 {
+    pub ram_enabled: bool,
+    pub bank1: u8,
+    pub bank2: u8,
+    pub mode: u8,
+    // MBC1M multicarts wire BANK2 to ROM address lines 18-19 instead of 19-20,
+    // so only the low 4 bits of BANK1 are used
+    pub multicart: bool,
+}
+
+impl Mbc1 {
+    pub fn new(rom: &[u8]) -> Mbc1 {
+        Mbc1 {
+            ram_enabled: false,
+            bank1: 1,
+            bank2: 0,
+            mode: 0,
+            multicart: Mbc1::is_multicart(rom),
+        }
+    }
+
+    // MBC1M carts are 1 MiB and repeat the Nintendo logo at the start of bank 0x10,
+    // where the header of the second game lives
+    fn is_multicart(rom: &[u8]) -> bool {
+        if rom.len() != 64 * ROM_BANK_SIZE {
+            return false;
+        }
+
+        let logo = &rom[0x0104..0x0134];
+        let second_header = 0x10 * ROM_BANK_SIZE + 0x0104;
+        &rom[second_header..second_header + logo.len()] == logo
+    }
+
+    fn bank1_bits(&self) -> u32 {
+        if self.multicart {
+            4
+        } else {
+            5
+        }
+    }
+
+    fn zero_bank(&self) -> usize {
+        if self.mode == 1 {
+            (self.bank2 as usize) << self.bank1_bits()
+        } else {
+            0
+        }
+    }
+
+    fn high_bank(&self) -> usize {
+        let bank1 = self.bank1 as usize & ((1 << self.bank1_bits()) - 1);
+        ((self.bank2 as usize) << self.bank1_bits()) | bank1
+    }
+
+    fn ram_bank(&self) -> usize {
+        if self.mode == 1 {
+            self.bank2 as usize
+        } else {
+            0
+        }
+    }
+}
+
+impl MemoryBankController for Mbc1 {
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(rom, self.zero_bank(), addr),
+            _ => read_rom_bank(rom, self.high_bank(), addr),
+        }
+    }
+
+    fn rom_bank(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => self.zero_bank(),
+            _ => self.high_bank(),
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                // the zero check happens on the full 5-bit register, so banks
+                // 0x20/0x40/0x60 can't be mapped at 0x4000 on regular carts
+                self.bank1 = value & 0x1F;
+                if self.bank1 == 0 {
+                    self.bank1 = 1;
+                }
+            }
+            0x4000..=0x5FFF => self.bank2 = value & 0x03,
+            _ => self.mode = value & 0x01,
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8 {
+        if !self.ram_enabled || ram.is_empty() {
+            return 0xFF;
+        }
+        ram[ram_bank_offset(ram, self.ram_bank(), addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        if !self.ram_enabled || ram.is_empty() {
+            return false;
+        }
+        ram[ram_bank_offset(ram, self.ram_bank(), addr)] = value;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mbc1;
+    use crate::cartridge::{MemoryBankController, ROM_BANK_SIZE};
+    use crate::mbc::{banked_rom, mapped_bank};
+
+    #[test]
+    fn bank_zero_maps_bank_one() {
+        let rom = banked_rom(128);
+        let mut mbc = Mbc1::new(&rom);
+        mbc.write_register(0x2000, 0x00);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 1);
+
+        // the zero check only sees the 5-bit BANK1, so 0x20 becomes 0x21
+        mbc.write_register(0x4000, 0x01);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x21);
+        mbc.write_register(0x2000, 0x05);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x25);
+    }
+
+    #[test]
+    fn bank_numbers_wrap_to_the_rom_size() {
+        let rom = banked_rom(4);
+        let mut mbc = Mbc1::new(&rom);
+        mbc.write_register(0x2000, 0x06);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 2);
+    }
+
+    #[test]
+    fn mode_one_banks_the_zero_area_and_ram() {
+        let rom = banked_rom(128);
+        let mut mbc = Mbc1::new(&rom);
+        let mut ram = vec![0; 0x8000];
+        mbc.write_register(0x0000, 0x0A);
+        mbc.write_register(0x4000, 0x02);
+
+        assert_eq!(mapped_bank(&mbc, &rom, 0x0000), 0);
+        mbc.write_ram(&mut ram, 0xA000, 0x11);
+        assert_eq!(ram[0], 0x11);
+
+        mbc.write_register(0x6000, 0x01);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x0000), 0x40);
+        assert_eq!(mbc.rom_bank(0x0000), 0x40);
+        mbc.write_ram(&mut ram, 0xA000, 0x22);
+        assert_eq!(ram[0x4000], 0x22);
+    }
+
+    #[test]
+    fn ram_needs_enabling() {
+        let rom = banked_rom(2);
+        let mut mbc = Mbc1::new(&rom);
+        let mut ram = vec![0; 0x2000];
+        assert!(!mbc.write_ram(&mut ram, 0xA000, 0x42));
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0xFF);
+        assert_eq!(ram[0], 0);
+
+        mbc.write_register(0x0000, 0x1A);
+        assert!(mbc.write_ram(&mut ram, 0xA000, 0x42));
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0x42);
+
+        mbc.write_register(0x0000, 0x00);
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0xFF);
+    }
+
+    #[test]
+    fn multicart_uses_four_bank1_bits() {
+        let mut rom = banked_rom(64);
+        let logo: Vec<u8> = (0..0x30).collect();
+        rom[0x0104..0x0134].copy_from_slice(&logo);
+        rom[0x10 * ROM_BANK_SIZE + 0x0104..0x10 * ROM_BANK_SIZE + 0x0134].copy_from_slice(&logo);
+
+        let mut mbc = Mbc1::new(&rom);
+        assert!(mbc.multicart);
+        mbc.write_register(0x4000, 0x01);
+        mbc.write_register(0x2000, 0x13);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x13);
+        mbc.write_register(0x6000, 0x01);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x0000), 0x10);
+    }
+}
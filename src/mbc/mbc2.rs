@@ -0,0 +1,112 @@
+use crate::cartridge::{read_rom_bank, MemoryBankController};
+
+/*
+0x0000 - 0x3FFF: RAM enable when address bit 8 is clear, ROM bank number (4 bits) when set.
+0xA000 - 0xA1FF: Built-in 512 x 4-bit RAM, mirrored across 0xA000 - 0xBFFF.
+*/
+
+pub const MBC2_RAM_SIZE: usize = 0x200;
+
+pub struct Mbc2 {
+    pub ram_enabled: bool,
+    pub rom_bank: u8,
+}
+
+impl Mbc2 {
+    pub fn new() -> Mbc2 {
+        Mbc2 {
+            ram_enabled: false,
+            rom_bank: 1,
+        }
+    }
+}
+
+impl MemoryBankController for Mbc2 {
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, addr),
+            _ => read_rom_bank(rom, self.rom_bank as usize, addr),
+        }
+    }
+
+    fn rom_bank(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, value: u8) {
+        if addr > 0x3FFF {
+            return;
+        }
+
+        if addr & 0x0100 == 0 {
+            self.ram_enabled = value & 0x0F == 0x0A;
+        } else {
+            self.rom_bank = value & 0x0F;
+            if self.rom_bank == 0 {
+                self.rom_bank = 1;
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+        // only the low nibble exists, the upper bits read back as 1
+        ram[addr as usize & (MBC2_RAM_SIZE - 1)] | 0xF0
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+        ram[addr as usize & (MBC2_RAM_SIZE - 1)] = value & 0x0F;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mbc2, MBC2_RAM_SIZE};
+    use crate::cartridge::MemoryBankController;
+    use crate::mbc::{banked_rom, mapped_bank};
+
+    #[test]
+    fn address_bit_8_picks_the_register() {
+        let rom = banked_rom(16);
+        let mut mbc = Mbc2::new();
+
+        mbc.write_register(0x2100, 0x05);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 5);
+        // bit 8 clear: RAM enable, the bank is unchanged
+        mbc.write_register(0x2000, 0x07);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 5);
+        assert!(!mbc.ram_enabled);
+        mbc.write_register(0x3EFF, 0x0A);
+        assert!(mbc.ram_enabled);
+
+        mbc.write_register(0x0100, 0x10);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 1);
+        // writes above 0x3FFF do nothing
+        mbc.write_register(0x4100, 0x03);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 1);
+    }
+
+    #[test]
+    fn ram_is_four_bits_wide_and_mirrored() {
+        let mut mbc = Mbc2::new();
+        let mut ram = vec![0; MBC2_RAM_SIZE];
+        assert!(!mbc.write_ram(&mut ram, 0xA000, 0x0C));
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0xFF);
+
+        mbc.write_register(0x0000, 0x0A);
+        assert!(mbc.write_ram(&mut ram, 0xA010, 0xAB));
+        assert_eq!(ram[0x10], 0x0B);
+        assert_eq!(mbc.read_ram(&ram, 0xA010), 0xFB);
+        assert_eq!(mbc.read_ram(&ram, 0xA210), 0xFB);
+        assert_eq!(mbc.read_ram(&ram, 0xBE10), 0xFB);
+    }
+}
@@ -0,0 +1,515 @@
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::cartridge::{ram_bank_offset, read_rom_bank, MemoryBankController};
+
+/*
+0x0000 - 0x1FFF: RAM and RTC enable (0x0A in the low nibble enables).
+0x2000 - 0x3FFF: ROM bank number (7 bits).
+0x4000 - 0x5FFF: RAM bank number (0x00 - 0x03) or RTC register select (0x08 - 0x0C).
+0x6000 - 0x7FFF: Latch clock data (write 0x00 then 0x01).
+*/
+
+const CYCLES_PER_SECOND: u32 = 4_194_304;
+
+// RTC footer appended to .sav files: 5 current + 5 latched registers as
+// little-endian u32s, then a little-endian u64 unix timestamp (older tools
+// wrote a u32 timestamp, giving 44 bytes)
+pub const RTC_SAVE_SIZE: usize = 48;
+const RTC_SAVE_SIZE_LEGACY: usize = 44;
+
+const DH_DAY_HIGH: u8 = 0x01;
+const DH_HALT: u8 = 0x40;
+const DH_DAY_CARRY: u8 = 0x80;
+
+#[derive(Clone, Copy, Default)]
+pub struct RtcRegisters {
+    pub seconds: u8,
+    pub minutes: u8,
+    pub hours: u8,
+    pub day_low: u8,
+    pub day_high: u8, // bit 0: day counter bit 8, bit 6: halt, bit 7: day counter carry
+}
+
+impl RtcRegisters {
+    fn read(&self, select: u8) -> u8 {
+        match select {
+            0x08 => self.seconds,
+            0x09 => self.minutes,
+            0x0A => self.hours,
+            0x0B => self.day_low,
+            0x0C => self.day_high,
+            _ => 0xFF,
+        }
+    }
+
+    fn to_save(self) -> [u32; 5] {
+        [
+            self.seconds as u32,
+            self.minutes as u32,
+            self.hours as u32,
+            self.day_low as u32,
+            self.day_high as u32,
+        ]
+    }
+
+    fn from_save(values: &[u32]) -> RtcRegisters {
+        RtcRegisters {
+            seconds: values[0] as u8 & 0x3F,
+            minutes: values[1] as u8 & 0x3F,
+            hours: values[2] as u8 & 0x1F,
+            day_low: values[3] as u8,
+            day_high: values[4] as u8 & (DH_DAY_HIGH | DH_HALT | DH_DAY_CARRY),
+        }
+    }
+
+    fn days(&self) -> u32 {
+        (((self.day_high & DH_DAY_HIGH) as u32) << 8) | self.day_low as u32
+    }
+
+    fn set_days(&mut self, days: u32) {
+        self.day_low = days as u8;
+        self.day_high = (self.day_high & !DH_DAY_HIGH) | ((days >> 8) as u8 & DH_DAY_HIGH);
+    }
+
+    fn in_range(&self) -> bool {
+        self.seconds < 60 && self.minutes < 60 && self.hours < 24
+    }
+
+    // advances by one second the way the counter chain does, including the
+    // wrap of out-of-range values written by software without carrying
+    fn tick(&mut self) {
+        self.seconds = (self.seconds + 1) & 0x3F;
+        if self.seconds != 60 {
+            return;
+        }
+        self.seconds = 0;
+
+        self.minutes = (self.minutes + 1) & 0x3F;
+        if self.minutes != 60 {
+            return;
+        }
+        self.minutes = 0;
+
+        self.hours = (self.hours + 1) & 0x1F;
+        if self.hours != 24 {
+            return;
+        }
+        self.hours = 0;
+
+        self.add_days(1);
+    }
+
+    fn add_days(&mut self, days: u32) {
+        let total = self.days() + days;
+        if total > 0x1FF {
+            self.day_high |= DH_DAY_CARRY;
+        }
+        self.set_days(total & 0x1FF);
+    }
+
+    fn advance(&mut self, mut seconds: u64) {
+        while seconds > 0 && !self.in_range() {
+            self.tick();
+            seconds -= 1;
+        }
+        if seconds == 0 {
+            return;
+        }
+
+        let total =
+            self.seconds as u64 + self.minutes as u64 * 60 + self.hours as u64 * 3600 + seconds;
+
+        self.seconds = (total % 60) as u8;
+        self.minutes = (total / 60 % 60) as u8;
+        self.hours = (total / 3600 % 24) as u8;
+
+        let days = total / 86400;
+        if days > 0x1FF {
+            self.day_high |= DH_DAY_CARRY;
+        }
+        self.add_days((days & 0x1FF) as u32);
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum RtcClock {
+    // advanced from emulated CPU cycles, so runs are deterministic
+    Emulated,
+    // catches up with host wall time whenever the game touches the clock
+    Host,
+}
+
+pub struct Rtc {
+    pub registers: RtcRegisters,
+    pub latched: RtcRegisters,
+    pub clock: RtcClock,
+    pub cycles: u32,
+    pub last_sync: u64, // unix timestamp of the last host sync
+    latch_armed: bool,
+}
+
+impl Rtc {
+    pub fn new() -> Rtc {
+        Rtc {
+            registers: RtcRegisters::default(),
+            latched: RtcRegisters::default(),
+            clock: RtcClock::Emulated,
+            cycles: 0,
+            last_sync: Rtc::unix_time(),
+            latch_armed: false,
+        }
+    }
+
+    pub fn unix_time() -> u64 {
+        SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0)
+    }
+
+    pub fn halted(&self) -> bool {
+        self.registers.day_high & DH_HALT != 0
+    }
+
+    pub fn step(&mut self, cycles: u32) {
+        if self.clock != RtcClock::Emulated || self.halted() {
+            return;
+        }
+
+        self.cycles += cycles;
+        while self.cycles >= CYCLES_PER_SECOND {
+            self.cycles -= CYCLES_PER_SECOND;
+            self.registers.tick();
+        }
+    }
+
+    pub fn sync_to_host(&mut self) {
+        let now = Rtc::unix_time();
+        if self.clock == RtcClock::Host && !self.halted() {
+            self.registers.advance(now.saturating_sub(self.last_sync));
+        }
+        self.last_sync = now;
+    }
+
+    pub fn set_clock(&mut self, clock: RtcClock) {
+        self.sync_to_host();
+        self.clock = clock;
+        self.cycles = 0;
+        self.last_sync = Rtc::unix_time();
+    }
+
+    pub fn save(&mut self) -> Vec<u8> {
+        self.sync_to_host();
+
+        let mut data = Vec::with_capacity(RTC_SAVE_SIZE);
+        for value in self
+            .registers
+            .to_save()
+            .iter()
+            .chain(self.latched.to_save().iter())
+        {
+            data.extend_from_slice(&value.to_le_bytes());
+        }
+
+        let timestamp = match self.clock {
+            RtcClock::Host => self.last_sync,
+            RtcClock::Emulated => Rtc::unix_time(),
+        };
+        data.extend_from_slice(&timestamp.to_le_bytes());
+
+        data
+    }
+
+    // restores the clock from a save footer, returns false if the footer is malformed
+    pub fn load(&mut self, data: &[u8]) -> bool {
+        if data.len() != RTC_SAVE_SIZE && data.len() != RTC_SAVE_SIZE_LEGACY {
+            return false;
+        }
+
+        let values: Vec<u32> = data[..40]
+            .chunks_exact(4)
+            .map(|chunk| u32::from_le_bytes([chunk[0], chunk[1], chunk[2], chunk[3]]))
+            .collect();
+
+        let mut timestamp = [0u8; 8];
+        timestamp[..data.len() - 40].copy_from_slice(&data[40..]);
+
+        self.registers = RtcRegisters::from_save(&values[..5]);
+        self.latched = RtcRegisters::from_save(&values[5..]);
+        self.cycles = 0;
+
+        // in host mode the clock keeps running while the emulator is closed
+        self.last_sync = u64::from_le_bytes(timestamp);
+        self.sync_to_host();
+
+        true
+    }
+
+    fn write_latch(&mut self, value: u8) {
+        if self.latch_armed && value == 0x01 {
+            self.sync_to_host();
+            self.latched = self.registers;
+        }
+        self.latch_armed = value == 0x00;
+    }
+
+    fn write_register(&mut self, select: u8, value: u8) {
+        self.sync_to_host();
+
+        let registers = &mut self.registers;
+        match select {
+            0x08 => {
+                registers.seconds = value & 0x3F;
+                // writing seconds resets the sub-second divider
+                self.cycles = 0;
+            }
+            0x09 => registers.minutes = value & 0x3F,
+            0x0A => registers.hours = value & 0x1F,
+            0x0B => registers.day_low = value,
+            0x0C => registers.day_high = value & (DH_DAY_HIGH | DH_HALT | DH_DAY_CARRY),
+            _ => (),
+        }
+    }
+}
+
+pub struct Mbc3 {
+    pub ram_enabled: bool,
+    pub rom_bank: u8,
+    pub ram_select: u8,
+    pub rtc: Option<Rtc>,
+}
+
+impl Mbc3 {
+    pub fn new(has_timer: bool) -> Mbc3 {
+        Mbc3 {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_select: 0,
+            rtc: if has_timer { Some(Rtc::new()) } else { None },
+        }
+    }
+}
+
+impl MemoryBankController for Mbc3 {
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, addr),
+            _ => read_rom_bank(rom, self.rom_bank as usize, addr),
+        }
+    }
+
+    fn rom_bank(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value & 0x0F == 0x0A,
+            0x2000..=0x3FFF => {
+                self.rom_bank = value & 0x7F;
+                if self.rom_bank == 0 {
+                    self.rom_bank = 1;
+                }
+            }
+            0x4000..=0x5FFF => self.ram_select = value & 0x0F,
+            _ => {
+                if let Some(rtc) = self.rtc.as_mut() {
+                    rtc.write_latch(value);
+                }
+            }
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8 {
+        if !self.ram_enabled {
+            return 0xFF;
+        }
+
+        match (self.ram_select, self.rtc.as_ref()) {
+            (0x00..=0x03, _) if !ram.is_empty() => {
+                ram[ram_bank_offset(ram, self.ram_select as usize, addr)]
+            }
+            (0x08..=0x0C, Some(rtc)) => rtc.latched.read(self.ram_select),
+            _ => 0xFF,
+        }
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        if !self.ram_enabled {
+            return false;
+        }
+
+        match (self.ram_select, self.rtc.as_mut()) {
+            (0x00..=0x03, _) if !ram.is_empty() => {
+                ram[ram_bank_offset(ram, self.ram_select as usize, addr)] = value;
+                true
+            }
+            (0x08..=0x0C, Some(rtc)) => {
+                rtc.write_register(self.ram_select, value);
+                true
+            }
+            _ => false,
+        }
+    }
+
+    fn step(&mut self, cycles: u32) {
+        if let Some(rtc) = self.rtc.as_mut() {
+            rtc.step(cycles);
+        }
+    }
+
+    fn rtc(&self) -> Option<&Rtc> {
+        self.rtc.as_ref()
+    }
+
+    fn rtc_mut(&mut self) -> Option<&mut Rtc> {
+        self.rtc.as_mut()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{Mbc3, Rtc, RTC_SAVE_SIZE};
+    use crate::cartridge::{Cartridge, MemoryBankController};
+    use crate::mbc::{banked_rom, mapped_bank};
+
+    const SECOND: u32 = super::CYCLES_PER_SECOND;
+
+    fn select_and_write(mbc: &mut Mbc3, select: u8, value: u8) {
+        mbc.write_register(0x4000, select);
+        mbc.write_ram(&mut [], 0xA000, value);
+    }
+
+    fn latch_and_read(mbc: &mut Mbc3, select: u8) -> u8 {
+        mbc.write_register(0x6000, 0x00);
+        mbc.write_register(0x6000, 0x01);
+        mbc.write_register(0x4000, select);
+        mbc.read_ram(&[], 0xA000)
+    }
+
+    fn clock() -> Mbc3 {
+        let mut mbc = Mbc3::new(true);
+        mbc.write_register(0x0000, 0x0A);
+        mbc
+    }
+
+    #[test]
+    fn seven_bit_rom_bank_maps_zero_to_one() {
+        let rom = banked_rom(128);
+        let mut mbc = Mbc3::new(false);
+        mbc.write_register(0x2000, 0x00);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 1);
+        mbc.write_register(0x3FFF, 0xFF);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x7F);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x0000), 0);
+    }
+
+    #[test]
+    fn reads_come_from_the_latched_copy() {
+        let mut mbc = clock();
+        mbc.write_register(0x4000, 0x08);
+        mbc.step(3 * SECOND);
+        assert_eq!(mbc.read_ram(&[], 0xA000), 0);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 3);
+
+        // still 3 until the next 0 -> 1 latch sequence
+        mbc.step(SECOND);
+        mbc.write_register(0x6000, 0x01);
+        assert_eq!(mbc.read_ram(&[], 0xA000), 3);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 4);
+    }
+
+    #[test]
+    fn day_counter_rolls_over_into_the_carry() {
+        let mut mbc = clock();
+        select_and_write(&mut mbc, 0x08, 59);
+        select_and_write(&mut mbc, 0x09, 59);
+        select_and_write(&mut mbc, 0x0A, 23);
+        select_and_write(&mut mbc, 0x0B, 0xFF);
+        select_and_write(&mut mbc, 0x0C, 0x01);
+
+        mbc.step(SECOND);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 0);
+        assert_eq!(latch_and_read(&mut mbc, 0x0A), 0);
+        assert_eq!(latch_and_read(&mut mbc, 0x0B), 0);
+        assert_eq!(latch_and_read(&mut mbc, 0x0C), 0x80);
+    }
+
+    #[test]
+    fn out_of_range_values_wrap_without_carrying() {
+        let mut mbc = clock();
+        select_and_write(&mut mbc, 0x08, 63);
+        mbc.step(SECOND);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 0);
+        assert_eq!(latch_and_read(&mut mbc, 0x09), 0);
+    }
+
+    #[test]
+    fn halt_stops_the_clock() {
+        let mut mbc = clock();
+        select_and_write(&mut mbc, 0x0C, 0x40);
+        mbc.step(10 * SECOND);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 0);
+
+        select_and_write(&mut mbc, 0x0C, 0x00);
+        mbc.step(SECOND);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 1);
+    }
+
+    #[test]
+    fn writing_seconds_resets_the_divider() {
+        let mut mbc = clock();
+        mbc.step(SECOND - 4);
+        select_and_write(&mut mbc, 0x08, 10);
+        mbc.step(8);
+        assert_eq!(latch_and_read(&mut mbc, 0x08), 10);
+    }
+
+    #[test]
+    fn save_footer_round_trips() {
+        let mut rtc = Rtc::new();
+        rtc.registers.seconds = 12;
+        rtc.registers.hours = 5;
+        rtc.registers.day_high = 0xC1;
+        rtc.latched.minutes = 34;
+        let data = rtc.save();
+        assert_eq!(data.len(), RTC_SAVE_SIZE);
+
+        let mut loaded = Rtc::new();
+        assert!(loaded.load(&data));
+        assert_eq!(loaded.registers.seconds, 12);
+        assert_eq!(loaded.registers.hours, 5);
+        assert_eq!(loaded.registers.day_high, 0xC1);
+        assert_eq!(loaded.latched.minutes, 34);
+
+        // older tools wrote a 32-bit timestamp
+        assert!(loaded.load(&data[..44]));
+        assert!(!loaded.load(&data[..40]));
+    }
+
+    #[test]
+    fn sav_files_carry_ram_and_the_footer() {
+        // MBC3+TIMER+RAM+BATTERY with 8 KiB of RAM
+        let mut rom = vec![0; 0x8000];
+        rom[0x147] = 0x10;
+        rom[0x149] = 0x02;
+
+        let mut cartridge = Cartridge::new(rom.clone()).unwrap();
+        cartridge.ram[0] = 0x5A;
+        cartridge.mbc.rtc_mut().unwrap().registers.minutes = 42;
+        let data = cartridge.save_data();
+        assert_eq!(data.len(), 0x2000 + RTC_SAVE_SIZE);
+
+        let mut restored = Cartridge::new(rom).unwrap();
+        assert_eq!(restored.load_save_data(&data), None);
+        assert_eq!(restored.ram[0], 0x5A);
+        assert_eq!(restored.mbc.rtc().unwrap().registers.minutes, 42);
+
+        // a truncated footer still restores RAM but is reported
+        assert!(restored.load_save_data(&data[..0x2000 + 10]).is_some());
+        assert_eq!(restored.ram[0], 0x5A);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::cartridge::{ram_bank_offset, read_rom_bank, MemoryBankController};
+
+/*
+0x0000 - 0x1FFF: RAM enable (exactly 0x0A enables).
+0x2000 - 0x2FFF: ROM bank number, lower 8 bits.
+0x3000 - 0x3FFF: ROM bank number, bit 8.
+0x4000 - 0x5FFF: RAM bank number (0x00 - 0x0F); on rumble carts bit 3 drives the motor.
+*/
+
+pub struct Mbc5 {
+    pub ram_enabled: bool,
+    pub rom_bank: u16,
+    pub ram_bank: u8,
+    pub has_rumble: bool,
+    pub rumble: bool,
+}
+
+impl Mbc5 {
+    pub fn new(has_rumble: bool) -> Mbc5 {
+        Mbc5 {
+            ram_enabled: false,
+            rom_bank: 1,
+            ram_bank: 0,
+            has_rumble,
+            rumble: false,
+        }
+    }
+}
+
+impl MemoryBankController for Mbc5 {
+    fn read_rom(&self, rom: &[u8], addr: u16) -> u8 {
+        match addr {
+            0x0000..=0x3FFF => read_rom_bank(rom, 0, addr),
+            _ => read_rom_bank(rom, self.rom_bank as usize, addr),
+        }
+    }
+
+    fn rom_bank(&self, addr: u16) -> usize {
+        match addr {
+            0x0000..=0x3FFF => 0,
+            _ => self.rom_bank as usize,
+        }
+    }
+
+    fn write_register(&mut self, addr: u16, value: u8) {
+        match addr {
+            0x0000..=0x1FFF => self.ram_enabled = value == 0x0A,
+            // unlike MBC1/MBC3, bank 0 can be mapped at 0x4000
+            0x2000..=0x2FFF => self.rom_bank = (self.rom_bank & 0x100) | value as u16,
+            0x3000..=0x3FFF => {
+                self.rom_bank = (self.rom_bank & 0xFF) | (((value & 0x01) as u16) << 8)
+            }
+            0x4000..=0x5FFF => {
+                if self.has_rumble {
+                    self.rumble = value & 0x08 != 0;
+                    self.ram_bank = value & 0x07;
+                } else {
+                    self.ram_bank = value & 0x0F;
+                }
+            }
+            _ => (),
+        }
+    }
+
+    fn read_ram(&self, ram: &[u8], addr: u16) -> u8 {
+        if !self.ram_enabled || ram.is_empty() {
+            return 0xFF;
+        }
+        ram[ram_bank_offset(ram, self.ram_bank as usize, addr)]
+    }
+
+    fn write_ram(&mut self, ram: &mut [u8], addr: u16, value: u8) -> bool {
+        if !self.ram_enabled || ram.is_empty() {
+            return false;
+        }
+        ram[ram_bank_offset(ram, self.ram_bank as usize, addr)] = value;
+        true
+    }
+
+    fn rumble(&self) -> bool {
+        self.rumble
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Mbc5;
+    use crate::cartridge::MemoryBankController;
+    use crate::mbc::{banked_rom, mapped_bank};
+
+    #[test]
+    fn nine_bit_rom_bank_including_zero() {
+        let rom = banked_rom(512);
+        let mut mbc = Mbc5::new(false);
+        mbc.write_register(0x2000, 0x00);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0);
+
+        mbc.write_register(0x3000, 0x01);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x100);
+        mbc.write_register(0x2000, 0xFF);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0x1FF);
+        mbc.write_register(0x3000, 0x00);
+        assert_eq!(mapped_bank(&mbc, &rom, 0x4000), 0xFF);
+    }
+
+    #[test]
+    fn ram_enable_needs_exactly_0x0a() {
+        let mut mbc = Mbc5::new(false);
+        let mut ram = vec![0; 0x2000];
+        mbc.write_register(0x0000, 0x1A);
+        assert!(!mbc.write_ram(&mut ram, 0xA000, 0x42));
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0xFF);
+
+        mbc.write_register(0x0000, 0x0A);
+        assert!(mbc.write_ram(&mut ram, 0xA000, 0x42));
+        assert_eq!(mbc.read_ram(&ram, 0xA000), 0x42);
+    }
+
+    #[test]
+    fn ram_banks() {
+        let mut mbc = Mbc5::new(false);
+        let mut ram = vec![0; 0x20000];
+        mbc.write_register(0x0000, 0x0A);
+        mbc.write_register(0x4000, 0x0F);
+        mbc.write_ram(&mut ram, 0xA001, 0x42);
+        assert_eq!(ram[0xF * 0x2000 + 1], 0x42);
+    }
+
+    #[test]
+    fn rumble_takes_bit_3_of_the_ram_bank() {
+        let mut mbc = Mbc5::new(true);
+        let mut ram = vec![0; 0x8000];
+        mbc.write_register(0x0000, 0x0A);
+        mbc.write_register(0x4000, 0x09);
+        assert!(mbc.rumble());
+        mbc.write_ram(&mut ram, 0xA000, 0x42);
+        assert_eq!(ram[0x2000], 0x42);
+
+        mbc.write_register(0x4000, 0x01);
+        assert!(!mbc.rumble());
+
+        // without a motor bit 3 is part of the bank number
+        let mut mbc = Mbc5::new(false);
+        mbc.write_register(0x4000, 0x09);
+        assert!(!mbc.rumble());
+        assert_eq!(mbc.ram_bank, 0x09);
+    }
+}
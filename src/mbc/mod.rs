@@ -0,0 +1,19 @@
+// test helpers shared by the mapper modules
+
+use crate::cartridge::{MemoryBankController, ROM_BANK_SIZE};
+
+// every 16-bit word of a bank holds its bank number, little-endian
+pub fn banked_rom(banks: usize) -> Vec<u8> {
+    let mut rom = Vec::with_capacity(banks * ROM_BANK_SIZE);
+    for bank in 0..banks {
+        for _ in 0..ROM_BANK_SIZE / 2 {
+            rom.extend_from_slice(&(bank as u16).to_le_bytes());
+        }
+    }
+    rom
+}
+
+// the number of the bank visible at addr, which must be even
+pub fn mapped_bank(mbc: &dyn MemoryBankController, rom: &[u8], addr: u16) -> u16 {
+    u16::from_le_bytes([mbc.read_rom(rom, addr), mbc.read_rom(rom, addr + 1)])
+}
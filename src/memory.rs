@@ -12,42 +12,169 @@
 0xFFFF: Interrupt Enable Register.
 */
 
+use crate::{
+    apu::APU,
+    cartridge::Cartridge,
+    device::DeviceEvent,
+    joypad::{Buttons, Joypad},
+    model::Model,
+    timer::Timer,
+};
+
+// the DMG boot ROM covers 0x0000 - 0x00FF; the CGB one also 0x0200 - 0x08FF, leaving
+// the cartridge header at 0x0100 - 0x01FF visible
+pub const DMG_BOOT_ROM_SIZE: usize = 0x100;
+pub const CGB_BOOT_ROM_SIZE: usize = 0x900;
+
+// OAM DMA copies 160 bytes from (value << 8) into OAM, one byte per M-cycle
+pub struct OamDma {
+    pub source: u16,
+    pub index: u16,
+    // M-cycles left before the first byte is copied
+    pub delay: u8,
+    // started while another transfer was running, which keeps the bus blocked
+    // through the delay
+    pub restarted: bool,
+}
+
 pub struct Memory {
-    pub rom: [u8; 0x8000],  // 0x0000 - 0x7FFFF
-    pub vram: [u8; 0x2000], // 0x8000 - 0x9FFF
-    pub eram: [u8; 0x2000], // 0xA000 - 0xBFFF
-    pub wram: [u8; 0x2000], // 0xC000 - 0xDFFF
+    pub cartridge: Cartridge, // 0x0000 - 0x7FFF, 0xA000 - 0xBFFF
+    pub vram: [u8; 0x2000],   // 0x8000 - 0x9FFF
+    pub wram: [u8; 0x2000],   // 0xC000 - 0xDFFF
     // 0xE000 - 0xFDFF (echo RAM)
     pub oam: [u8; 0xA0], // 0xFE00 - 0xFE9F
     // 0xFEA0 - 0xFEFF not usable
     pub io: [u8; 0x80],       // 0xFF00 - 0xFF7F
     pub hram: [u8; 0x7F],     // 0xFF80 - 0xFFFE
     pub interrupt_enable: u8, // 0xFFFF
+    pub timer: Timer,
+    pub joypad: Joypad,
+    pub dma: Option<OamDma>,
+    pub apu: APU,
+    pub events: Vec<DeviceEvent>,
+    // mapped over the cartridge until the first write to 0xFF50
+    pub boot_rom: Option<Vec<u8>>,
+    pub model: Model,
+    // CGB double speed mode, toggled by STOP with KEY1 (0xFF4D) armed
+    pub double_speed: bool,
+    // LY always reads 0x90, as Gameboy Doctor's reference logs assume
+    pub doctor: bool,
 }
 
 impl Memory {
     pub fn new() -> Memory {
         Memory {
-            rom: [0; 0x8000],
+            cartridge: Cartridge::empty(),
             vram: [0; 0x2000],
-            eram: [0; 0x2000],
             wram: [0; 0x2000],
             oam: [0; 0xA0],
             io: [0; 0x80],
             hram: [0; 0x7F],
             interrupt_enable: 0,
+            timer: Timer::new(),
+            joypad: Joypad::new(),
+            dma: None,
+            apu: APU::new(),
+            events: Vec::new(),
+            boot_rom: None,
+            model: Model::default(),
+            double_speed: false,
+            doctor: false,
+        }
+    }
+
+    pub fn set_model(&mut self, model: Model) {
+        self.model = model;
+        self.apu.model = model;
+    }
+
+    // leaves the I/O registers the way the model's boot ROM does
+    pub fn apply_post_boot(&mut self) {
+        let model = self.model;
+        for (addr, value) in model.post_boot_io() {
+            self.write_byte(addr, value);
+        }
+        self.io[0x46] = model.post_boot_dma();
+        self.timer.div = model.post_boot_div();
+    }
+
+    // queues a DeviceEvent::Warning for the frontend
+    pub fn warn(&mut self, message: String) {
+        self.events.push(DeviceEvent::Warning(message));
+    }
+
+    pub fn request_interrupt(&mut self, interrupt_bit: u8) {
+        self.io[0x0F] |= interrupt_bit;
+    }
+
+    pub fn set_buttons(&mut self, buttons: Buttons) {
+        if self.joypad.set_buttons(buttons) {
+            self.request_interrupt(0x10);
+        }
+    }
+
+    // true while any selected joypad line is pulled low by a pressed button
+    pub fn joypad_line_low(&self) -> bool {
+        self.joypad.read_byte() & 0x0F != 0x0F
+    }
+
+    pub fn step_timer(&mut self, cycles: u32) {
+        if self.timer.step(cycles) {
+            self.request_interrupt(0x04);
+        }
+    }
+
+    pub fn dma_active(&self) -> bool {
+        matches!(&self.dma, Some(dma) if dma.delay == 0 || dma.restarted)
+    }
+
+    pub fn step_dma(&mut self, cycles: u32) {
+        for _ in 0..cycles / 4 {
+            let Some(dma) = self.dma.as_mut() else {
+                return;
+            };
+
+            if dma.delay > 0 {
+                dma.delay -= 1;
+                continue;
+            }
+
+            let index = dma.index;
+            // sources above 0xDFFF read from the echo of WRAM
+            let mut source = dma.source + index;
+            if source >= 0xE000 {
+                source -= 0x2000;
+            }
+
+            dma.index += 1;
+            if dma.index == 0xA0 {
+                self.dma = None;
+            }
+
+            self.oam[index as usize] = self.read_byte(source);
+        }
+    }
+
+    fn read_boot_rom(&self, addr: u16) -> Option<u8> {
+        let boot_rom = self.boot_rom.as_ref()?;
+        match addr {
+            0x0000..=0x00FF => Some(boot_rom[addr as usize]),
+            0x0200..=0x08FF if boot_rom.len() == CGB_BOOT_ROM_SIZE => Some(boot_rom[addr as usize]),
+            _ => None,
         }
     }
 
     pub fn read_byte(&self, addr: u16) -> u8 {
         match addr {
-            0x0000..=0x7FFF => self.rom[addr as usize],
+            0x0000..=0x7FFF => self
+                .read_boot_rom(addr)
+                .unwrap_or_else(|| self.cartridge.read_rom(addr)),
             0x8000..=0x9FFF => self.vram[(addr - 0x8000) as usize],
-            0xA000..=0xBFFF => self.eram[(addr - 0xA000) as usize],
+            0xA000..=0xBFFF => self.cartridge.read_ram(addr),
             0xC000..=0xDFFF => self.wram[(addr - 0xC000) as usize],
             0xE000..=0xFDFF => self.wram[(addr - 0xE000) as usize], // Echo RAM mirrors WRAM
             0xFE00..=0xFE9F => self.oam[(addr - 0xFE00) as usize],
-            0xFF00..=0xFF7F => self.io[(addr - 0xFF00) as usize],
+            0xFF00..=0xFF7F => self.read_io(addr),
             0xFF80..=0xFFFE => self.hram[(addr - 0xFF80) as usize],
             0xFFFF => self.interrupt_enable,
             _ => 0xFF,
@@ -56,16 +183,157 @@ impl Memory {
 
     pub fn write_byte(&mut self, address: u16, value: u8) {
         match address {
-            0x0000..=0x7FFF => self.rom[address as usize] = value,
+            0x0000..=0x7FFF => {
+                let rumble = self.cartridge.rumble();
+                self.cartridge.write_rom(address, value);
+                if self.cartridge.rumble() != rumble {
+                    self.events.push(DeviceEvent::Rumble(!rumble));
+                }
+            }
             0x8000..=0x9FFF => self.vram[(address - 0x8000) as usize] = value,
-            0xA000..=0xBFFF => self.eram[(address - 0xA000) as usize] = value,
+            0xA000..=0xBFFF => self.cartridge.write_ram(address, value),
             0xC000..=0xDFFF => self.wram[(address - 0xC000) as usize] = value,
             0xE000..=0xFDFF => self.wram[(address - 0xE000) as usize] = value, // Echo RAM mirrors WRAM
             0xFE00..=0xFE9F => self.oam[(address - 0xFE00) as usize] = value,
-            0xFF00..=0xFF7F => self.io[(address - 0xFF00) as usize] = value,
+            0xFF00..=0xFF7F => self.write_io(address, value),
             0xFF80..=0xFFFE => self.hram[(address - 0xFF80) as usize] = value,
             0xFFFF => self.interrupt_enable = value,
             _ => {}
         }
     }
+
+    // the PPU's side of STAT: mode in bits 0-1, LY=LYC in bit 2
+    pub fn set_stat_mode(&mut self, mode: u8, coincidence: bool) {
+        self.io[0x41] = (self.io[0x41] & 0xF8) | ((coincidence as u8) << 2) | (mode & 0x03);
+    }
+
+    fn read_io(&self, addr: u16) -> u8 {
+        match addr {
+            0xFF00 => self.joypad.read_byte(),
+            0xFF04..=0xFF07 => self.timer.read_byte(addr),
+            0xFF10..=0xFF3F => self.apu.read_byte(addr),
+            // STAT bit 7 is unused and always reads 1
+            0xFF41 => self.io[0x41] | 0x80,
+            0xFF44 if self.doctor => 0x90,
+            0xFF4D if self.model.is_cgb() => {
+                0x7E | ((self.double_speed as u8) << 7) | (self.io[0x4D] & 0x01)
+            }
+            0xFF4D | 0xFF50 => 0xFF,
+            _ => self.io[(addr - 0xFF00) as usize],
+        }
+    }
+
+    fn write_io(&mut self, address: u16, value: u8) {
+        match address {
+            0xFF00 => {
+                if self.joypad.write_byte(value) {
+                    self.request_interrupt(0x10);
+                }
+            }
+            0xFF04..=0xFF07 => self.timer.write_byte(address, value),
+            0xFF10..=0xFF3F => self.apu.write_byte(address, value),
+            0xFF41 => {
+                // DMG-family STAT writes act as if every source was enabled for a cycle, so
+                // one during HBlank, VBlank or a LY=LYC match raises the interrupt
+                let stat = self.io[0x41];
+                let lcd_on = self.io[0x40] & 0x80 != 0;
+                if self.model.has_stat_write_bug()
+                    && lcd_on
+                    && (stat & 0x03 < 2 || stat & 0x04 != 0)
+                {
+                    self.request_interrupt(0x02);
+                }
+                // mode and coincidence bits are read-only
+                self.io[0x41] = (value & 0x78) | (stat & 0x07);
+            }
+            // KEY1: bit 0 arms a speed switch for the next STOP
+            0xFF4D => self.io[0x4D] = value & 0x01,
+            0xFF50 => {
+                // unmapping is one-way until the next power cycle
+                if value & 0x01 != 0 {
+                    self.boot_rom = None;
+                }
+            }
+            0xFF46 => {
+                self.io[0x46] = value;
+                self.dma = Some(OamDma {
+                    source: (value as u16) << 8,
+                    index: 0,
+                    delay: 1,
+                    restarted: self.dma_active(),
+                });
+            }
+            _ => self.io[(address - 0xFF00) as usize] = value,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Memory;
+    use crate::model::Model;
+
+    #[test]
+    fn cpu_stat_writes_keep_the_ppu_bits() {
+        let mut memory = Memory::new();
+        memory.set_stat_mode(3, true);
+        memory.write_byte(0xFF41, 0xFF);
+        assert_eq!(memory.read_byte(0xFF41), 0xFF);
+        memory.write_byte(0xFF41, 0x00);
+        assert_eq!(memory.read_byte(0xFF41), 0x87);
+
+        memory.set_stat_mode(0, false);
+        assert_eq!(memory.read_byte(0xFF41), 0x80);
+    }
+
+    #[test]
+    fn dmg_stat_write_bug_only_on_cpu_writes() {
+        let mut memory = Memory::new();
+        memory.write_byte(0xFF40, 0x91);
+        memory.set_stat_mode(0, false);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x02, 0);
+
+        memory.write_byte(0xFF41, 0x00);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x02, 0x02);
+
+        let mut memory = Memory::new();
+        memory.set_model(Model::Cgb);
+        memory.write_byte(0xFF40, 0x91);
+        memory.set_stat_mode(0, false);
+        memory.write_byte(0xFF41, 0x00);
+        assert_eq!(memory.read_byte(0xFF0F) & 0x02, 0);
+    }
+
+    #[test]
+    fn doctor_mode_stubs_ly() {
+        let mut memory = Memory::new();
+        memory.io[0x44] = 0x12;
+        assert_eq!(memory.read_byte(0xFF44), 0x12);
+        memory.doctor = true;
+        assert_eq!(memory.read_byte(0xFF44), 0x90);
+    }
+
+    #[test]
+    fn dma_blocks_the_bus_after_its_start_delay() {
+        let mut memory = Memory::new();
+        memory.write_byte(0xFF46, 0xC0);
+        assert!(!memory.dma_active());
+        memory.step_dma(4);
+        assert!(memory.dma_active());
+        memory.step_dma(4 * 0xA0);
+        assert!(!memory.dma_active());
+    }
+
+    #[test]
+    fn restarted_dma_stays_active_through_the_delay() {
+        let mut memory = Memory::new();
+        memory.write_byte(0xFF46, 0xC0);
+        memory.step_dma(4 * 10);
+
+        memory.write_byte(0xFF46, 0xC1);
+        assert!(memory.dma_active());
+        memory.step_dma(4);
+        assert!(memory.dma_active());
+        assert_eq!(memory.dma.as_ref().map(|dma| dma.index), Some(0));
+    }
 }
@@ -0,0 +1,349 @@
+use crate::{
+    cartridge::{Cartridge, CgbSupport},
+    registers::Registers,
+};
+
+/*
+Post-boot state per model, as left behind by each boot ROM:
+
+        A   F   B   C   D   E   H   L
+DMG0    01  00  FF  13  00  C1  84  03
+DMG     01  B0* 00  13  00  D8  01  4D
+MGB     FF  B0* 00  13  00  D8  01  4D
+SGB     01  00  00  14  00  00  C0  60
+SGB2    FF  00  00  14  00  00  C0  60
+CGB     11  80  00  00  FF  56  00  0D   (DMG carts: B* D=00 E=08 HL=007C)
+AGB     11  00  01  00  FF  56  00  0D   (DMG carts: B*+1 D=00 E=08 HL=007C)
+
+F*: H and C are clear only when the header checksum is 0x00.
+B*: sum of the title bytes for Nintendo-licensed carts, 0x00 otherwise.
+SP is 0xFFFE and PC 0x0100 everywhere.
+*/
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Model {
+    Dmg0,
+    #[default]
+    Dmg,
+    Mgb,
+    Sgb,
+    Sgb2,
+    Cgb,
+    Agb,
+}
+
+impl Model {
+    pub const ALL: [Model; 7] = [
+        Model::Dmg0,
+        Model::Dmg,
+        Model::Mgb,
+        Model::Sgb,
+        Model::Sgb2,
+        Model::Cgb,
+        Model::Agb,
+    ];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Model::Dmg0 => "dmg0",
+            Model::Dmg => "dmg",
+            Model::Mgb => "mgb",
+            Model::Sgb => "sgb",
+            Model::Sgb2 => "sgb2",
+            Model::Cgb => "cgb",
+            Model::Agb => "agb",
+        }
+    }
+
+    pub fn from_name(name: &str) -> Option<Model> {
+        Model::ALL
+            .into_iter()
+            .find(|model| model.name().eq_ignore_ascii_case(name))
+    }
+
+    // CGB-family hardware, also when running a DMG cartridge in compatibility mode
+    pub fn is_cgb(self) -> bool {
+        matches!(self, Model::Cgb | Model::Agb)
+    }
+
+    pub fn is_sgb(self) -> bool {
+        matches!(self, Model::Sgb | Model::Sgb2)
+    }
+
+    // DMG-family PPUs briefly see every STAT interrupt source enabled during a STAT write
+    pub fn has_stat_write_bug(self) -> bool {
+        !self.is_cgb()
+    }
+
+    // on DMG-family APUs wave RAM is only reachable while channel 3 is stopped, and
+    // length counters survive powering the APU off
+    pub fn has_dmg_apu(self) -> bool {
+        !self.is_cgb()
+    }
+
+    pub fn post_boot_registers(self, cartridge: &Cartridge) -> Registers {
+        let header = &cartridge.header;
+        let checksum_flags = if header.header_checksum == 0 {
+            0x80
+        } else {
+            0xB0
+        };
+        let cgb_mode = header.cgb_support != CgbSupport::None;
+        let title_sum = Model::licensed_title_sum(cartridge);
+
+        let (a, f, b, c, d, e, h, l) = match self {
+            Model::Dmg0 => (0x01, 0x00, 0xFF, 0x13, 0x00, 0xC1, 0x84, 0x03),
+            Model::Dmg => (0x01, checksum_flags, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D),
+            Model::Mgb => (0xFF, checksum_flags, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D),
+            Model::Sgb => (0x01, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60),
+            Model::Sgb2 => (0xFF, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60),
+            Model::Cgb if cgb_mode => (0x11, 0x80, 0x00, 0x00, 0xFF, 0x56, 0x00, 0x0D),
+            Model::Cgb => (0x11, 0x80, title_sum, 0x00, 0x00, 0x08, 0x00, 0x7C),
+            // the AGB boot ROM ends with an extra INC B, which also sets the flags
+            Model::Agb => {
+                let b = if cgb_mode {
+                    0x01
+                } else {
+                    title_sum.wrapping_add(1)
+                };
+                let zero = if b == 0 { 0x80 } else { 0x00 };
+                let half_carry = if b & 0x0F == 0 { 0x20 } else { 0x00 };
+                let f = zero | half_carry;
+                if cgb_mode {
+                    (0x11, f, b, 0x00, 0xFF, 0x56, 0x00, 0x0D)
+                } else {
+                    (0x11, f, b, 0x00, 0x00, 0x08, 0x00, 0x7C)
+                }
+            }
+        };
+
+        Registers {
+            a,
+            f,
+            b,
+            c,
+            d,
+            e,
+            h,
+            l,
+            sp: 0xFFFE,
+            pc: 0x0100,
+        }
+    }
+
+    // the CGB boot ROM hashes the title of Nintendo games to pick a DMG palette
+    fn licensed_title_sum(cartridge: &Cartridge) -> u8 {
+        let header = &cartridge.header;
+        let nintendo = header.old_licensee_code == 0x01
+            || (header.old_licensee_code == 0x33
+                && header.new_licensee_code.as_deref() == Some("01"));
+        if !nintendo {
+            return 0;
+        }
+        cartridge.rom[0x0134..=0x0143]
+            .iter()
+            .fold(0u8, |sum, &byte| sum.wrapping_add(byte))
+    }
+
+    // internal 16-bit divider when the boot ROM hands over; the upper byte is DIV.
+    // the SGB and CGB values depend on boot ROM timing and aren't pinned down
+    pub fn post_boot_div(self) -> u16 {
+        match self {
+            Model::Dmg0 => 0x1800,
+            Model::Dmg | Model::Mgb => 0xABCC,
+            _ => 0x0000,
+        }
+    }
+
+    // I/O registers in write order; NR52 comes first so the APU accepts the rest.
+    // NRx4 trigger bits are left out so the boot chime doesn't play again
+    pub fn post_boot_io(self) -> Vec<(u16, u8)> {
+        let mut io = vec![
+            (0xFF26, if self.is_sgb() { 0xF0 } else { 0xF1 }), // NR52
+            (0xFF00, 0x00),                                    // P1, reads back 0xCF
+            (0xFF01, 0x00),                                    // SB
+            (0xFF02, if self.is_cgb() { 0x7F } else { 0x7E }), // SC
+            (0xFF05, 0x00),                                    // TIMA
+            (0xFF06, 0x00),                                    // TMA
+            (0xFF07, 0xF8),                                    // TAC
+            (0xFF10, 0x80),                                    // NR10
+            (0xFF11, 0xBF),                                    // NR11
+            (0xFF12, 0xF3),                                    // NR12
+            (0xFF13, 0xFF),                                    // NR13
+            (0xFF14, 0x3F),                                    // NR14
+            (0xFF16, 0x3F),                                    // NR21
+            (0xFF17, 0x00),                                    // NR22
+            (0xFF18, 0xFF),                                    // NR23
+            (0xFF19, 0x3F),                                    // NR24
+            (0xFF1A, 0x7F),                                    // NR30
+            (0xFF1B, 0xFF),                                    // NR31
+            (0xFF1C, 0x9F),                                    // NR32
+            (0xFF1D, 0xFF),                                    // NR33
+            (0xFF1E, 0x3F),                                    // NR34
+            (0xFF20, 0xFF),                                    // NR41
+            (0xFF21, 0x00),                                    // NR42
+            (0xFF22, 0x00),                                    // NR43
+            (0xFF23, 0x3F),                                    // NR44
+            (0xFF24, 0x77),                                    // NR50
+            (0xFF25, 0xF3),                                    // NR51
+            (0xFF40, 0x91),                                    // LCDC
+            (0xFF41, 0x85),                                    // STAT
+            (0xFF42, 0x00),                                    // SCY
+            (0xFF43, 0x00),                                    // SCX
+            (0xFF45, 0x00),                                    // LYC
+            (0xFF47, 0xFC),                                    // BGP
+            (0xFF4A, 0x00),                                    // WY
+            (0xFF4B, 0x00),                                    // WX
+        ];
+        if !self.is_cgb() {
+            // OBP0/OBP1 are left uninitialized by the boot ROM; 0xFF is the usual value
+            io.push((0xFF48, 0xFF));
+            io.push((0xFF49, 0xFF));
+        }
+        // IF last, so flags raised by the writes above don't linger
+        io.push((0xFF0F, 0xE1));
+        io
+    }
+
+    // what the boot ROM leaves in DMA (0xFF46), set without starting a transfer
+    pub fn post_boot_dma(self) -> u8 {
+        if self.is_cgb() {
+            0x00
+        } else {
+            0xFF
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Model;
+    use crate::{cartridge::Cartridge, memory::Memory};
+
+    // a ROM-only cart; `licensee` is the old licensee code, or the new one after 0x33
+    fn cartridge(title: &[u8], licensee: &[u8], cgb_flag: u8, header_checksum: u8) -> Cartridge {
+        let mut rom = vec![0; 0x8000];
+        rom[0x0134..0x0134 + title.len()].copy_from_slice(title);
+        rom[0x0143] = cgb_flag;
+        match licensee {
+            [old] => rom[0x014B] = *old,
+            new => {
+                rom[0x014B] = 0x33;
+                rom[0x0144..0x0146].copy_from_slice(new);
+            }
+        }
+        rom[0x014D] = header_checksum;
+        Cartridge::new(rom).unwrap()
+    }
+
+    // A F B C D E H L, as in the table at the top
+    fn registers(model: Model, cartridge: &Cartridge) -> [u8; 8] {
+        let r = model.post_boot_registers(cartridge);
+        assert_eq!((r.sp, r.pc), (0xFFFE, 0x0100));
+        [r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l]
+    }
+
+    #[test]
+    fn post_boot_registers() {
+        // "TETRIS" sums to 0xDB, licensed by Nintendo
+        let dmg_cart = cartridge(b"TETRIS", &[0x01], 0x00, 0x0A);
+        let cgb_cart = cartridge(b"POKEMON CRY", b"01", 0x80, 0x3D);
+
+        let dmg_cases = [
+            (
+                Model::Dmg0,
+                [0x01, 0x00, 0xFF, 0x13, 0x00, 0xC1, 0x84, 0x03],
+            ),
+            (Model::Dmg, [0x01, 0xB0, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D]),
+            (Model::Mgb, [0xFF, 0xB0, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D]),
+            (Model::Sgb, [0x01, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60]),
+            (
+                Model::Sgb2,
+                [0xFF, 0x00, 0x00, 0x14, 0x00, 0x00, 0xC0, 0x60],
+            ),
+            (Model::Cgb, [0x11, 0x80, 0xDB, 0x00, 0x00, 0x08, 0x00, 0x7C]),
+            (Model::Agb, [0x11, 0x00, 0xDC, 0x00, 0x00, 0x08, 0x00, 0x7C]),
+        ];
+        for (model, expected) in dmg_cases {
+            assert_eq!(registers(model, &dmg_cart), expected, "{:?}", model);
+        }
+
+        let cgb_cases = [
+            (Model::Dmg, [0x01, 0xB0, 0x00, 0x13, 0x00, 0xD8, 0x01, 0x4D]),
+            (Model::Cgb, [0x11, 0x80, 0x00, 0x00, 0xFF, 0x56, 0x00, 0x0D]),
+            (Model::Agb, [0x11, 0x00, 0x01, 0x00, 0xFF, 0x56, 0x00, 0x0D]),
+        ];
+        for (model, expected) in cgb_cases {
+            assert_eq!(registers(model, &cgb_cart), expected, "{:?}", model);
+        }
+    }
+
+    #[test]
+    fn zero_header_checksum_clears_h_and_c() {
+        let cart = cartridge(b"TETRIS", &[0x01], 0x00, 0x00);
+        assert_eq!(registers(Model::Dmg, &cart)[1], 0x80);
+        assert_eq!(registers(Model::Mgb, &cart)[1], 0x80);
+        // the other boot ROMs don't look at it
+        assert_eq!(registers(Model::Dmg0, &cart)[1], 0x00);
+        assert_eq!(registers(Model::Cgb, &cart)[1], 0x80);
+    }
+
+    #[test]
+    fn agb_inc_b_sets_the_flags() {
+        // (title, title sum + 1, flags after INC B)
+        let cases: [(&[u8], u8, u8); 4] = [
+            (&[0xFF], 0x00, 0xA0),
+            (&[0x0F], 0x10, 0x20),
+            (&[0x10], 0x11, 0x00),
+            (&[0x7F, 0x80], 0x00, 0xA0),
+        ];
+        for (title, b, f) in cases {
+            let cart = cartridge(title, &[0x01], 0x00, 0x01);
+            let [_, flags, inc_b, ..] = registers(Model::Agb, &cart);
+            assert_eq!((inc_b, flags), (b, f), "{:02X?}", title);
+        }
+    }
+
+    #[test]
+    fn licensed_title_sum() {
+        let title = b"ABC";
+        let cases: [(&[u8], u8); 4] = [
+            (&[0x01], 0xC6),
+            (b"01", 0xC6),
+            (b"08", 0x00),
+            (&[0x0A], 0x00),
+        ];
+        for (licensee, sum) in cases {
+            let cart = cartridge(title, licensee, 0x00, 0x01);
+            assert_eq!(Model::licensed_title_sum(&cart), sum, "{:02X?}", licensee);
+        }
+
+        let cart = cartridge(&[0xFF; 15], &[0x01], 0x00, 0x01);
+        assert_eq!(Model::licensed_title_sum(&cart), 0xF1);
+    }
+
+    #[test]
+    fn post_boot_io() {
+        for model in Model::ALL {
+            let io = model.post_boot_io();
+            assert_eq!(io.first().map(|&(addr, _)| addr), Some(0xFF26));
+            assert_eq!(io.last(), Some(&(0xFF0F, 0xE1)));
+
+            let nr52 = if model.is_sgb() { 0xF0 } else { 0xF1 };
+            assert!(io.contains(&(0xFF26, nr52)), "{:?}", model);
+            let sc = if model.is_cgb() { 0x7F } else { 0x7E };
+            assert!(io.contains(&(0xFF02, sc)), "{:?}", model);
+            let has_obp = io.iter().any(|&(addr, _)| addr == 0xFF48 || addr == 0xFF49);
+            assert_eq!(has_obp, !model.is_cgb(), "{:?}", model);
+
+            let mut memory = Memory::new();
+            memory.set_model(model);
+            memory.apply_post_boot();
+            assert_eq!(memory.read_byte(0xFF00), 0xCF);
+            assert_eq!(memory.read_byte(0xFF0F), 0xE1);
+            assert_eq!(memory.read_byte(0xFF40), 0x91);
+            assert_eq!(memory.read_byte(0xFF41) & 0xF8, 0x80);
+            assert_eq!(memory.read_byte(0xFF47), 0xFC);
+        }
+    }
+}
@@ -0,0 +1,587 @@
+use crate::{cpu::CPU, registers::Register};
+
+/*
+Instruction table for the SM83, one entry per opcode: UNPREFIXED for single byte
+opcodes and CB_PREFIXED for the second byte after 0xCB.
+
+mnemonic   RGBDS syntax, with the operand written as a placeholder:
+           n8/n16 immediates, e8 signed offset, a8 for LDH's 0xFF00 + n8, a16 address
+length     bytes including the opcode (and the 0xCB prefix for CB_PREFIXED)
+cycles     T-cycles, with the branch not taken for conditional instructions
+flags      effect on Z N H C: the letter if computed, 0/1 if reset/set, - if untouched
+*/
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Operand {
+    None,
+    Imm8,
+    Imm16,
+    // signed 8-bit offset added to SP
+    Offset8,
+    // 0xFF00 + n8
+    HighAddr8,
+    Addr16,
+    // signed 8-bit jump relative to the next instruction
+    Relative8,
+}
+
+pub struct Instruction {
+    pub mnemonic: &'static str,
+    pub operand: Operand,
+    pub length: u8,
+    pub cycles: u8,
+    // cycles when a conditional branch is taken, same as cycles otherwise
+    pub branch_cycles: u8,
+    pub flags: &'static str,
+    pub execute: fn(&mut CPU),
+}
+
+impl Instruction {
+    const fn new(
+        mnemonic: &'static str,
+        operand: Operand,
+        length: u8,
+        cycles: u8,
+        branch_cycles: u8,
+        flags: &'static str,
+        execute: fn(&mut CPU),
+    ) -> Instruction {
+        Instruction {
+            mnemonic,
+            operand,
+            length,
+            cycles,
+            branch_cycles,
+            flags,
+            execute,
+        }
+    }
+
+    pub fn is_illegal(&self) -> bool {
+        self.mnemonic == "ILLEGAL"
+    }
+
+    pub fn is_conditional(&self) -> bool {
+        self.cycles != self.branch_cycles
+    }
+}
+
+#[rustfmt::skip]
+pub static UNPREFIXED: [Instruction; 256] = [
+    /* 00 */ Instruction::new("NOP", Operand::None, 1, 4, 4, "----", |cpu| cpu.nop()),
+    /* 01 */ Instruction::new("LD BC, n16", Operand::Imm16, 3, 12, 12, "----", |cpu| cpu.ld_16_imm2(Register::BC)),
+    /* 02 */ Instruction::new("LD [BC], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::BC, Register::A)),
+    /* 03 */ Instruction::new("INC BC", Operand::None, 1, 8, 8, "----", |cpu| cpu.inc_16(Register::BC)),
+    /* 04 */ Instruction::new("INC B", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::B)),
+    /* 05 */ Instruction::new("DEC B", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::B)),
+    /* 06 */ Instruction::new("LD B, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::B)),
+    /* 07 */ Instruction::new("RLCA", Operand::None, 1, 4, 4, "000C", |cpu| cpu.rlca()),
+    /* 08 */ Instruction::new("LD [a16], SP", Operand::Addr16, 3, 20, 20, "----", |cpu| cpu.ld_memimm2_sp()),
+    /* 09 */ Instruction::new("ADD HL, BC", Operand::None, 1, 8, 8, "-0HC", |cpu| cpu.add_16_16(Register::HL, Register::BC)),
+    /* 0A */ Instruction::new("LD A, [BC]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::A, Register::BC)),
+    /* 0B */ Instruction::new("DEC BC", Operand::None, 1, 8, 8, "----", |cpu| cpu.dec_16(Register::BC)),
+    /* 0C */ Instruction::new("INC C", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::C)),
+    /* 0D */ Instruction::new("DEC C", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::C)),
+    /* 0E */ Instruction::new("LD C, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::C)),
+    /* 0F */ Instruction::new("RRCA", Operand::None, 1, 4, 4, "000C", |cpu| cpu.rrca()),
+    /* 10 */ Instruction::new("STOP", Operand::None, 2, 4, 4, "----", |cpu| cpu.stop()),
+    /* 11 */ Instruction::new("LD DE, n16", Operand::Imm16, 3, 12, 12, "----", |cpu| cpu.ld_16_imm2(Register::DE)),
+    /* 12 */ Instruction::new("LD [DE], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::DE, Register::A)),
+    /* 13 */ Instruction::new("INC DE", Operand::None, 1, 8, 8, "----", |cpu| cpu.inc_16(Register::DE)),
+    /* 14 */ Instruction::new("INC D", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::D)),
+    /* 15 */ Instruction::new("DEC D", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::D)),
+    /* 16 */ Instruction::new("LD D, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::D)),
+    /* 17 */ Instruction::new("RLA", Operand::None, 1, 4, 4, "000C", |cpu| cpu.rla()),
+    /* 18 */ Instruction::new("JR e8", Operand::Relative8, 2, 12, 12, "----", |cpu| cpu.jr_imm1()),
+    /* 19 */ Instruction::new("ADD HL, DE", Operand::None, 1, 8, 8, "-0HC", |cpu| cpu.add_16_16(Register::HL, Register::DE)),
+    /* 1A */ Instruction::new("LD A, [DE]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::A, Register::DE)),
+    /* 1B */ Instruction::new("DEC DE", Operand::None, 1, 8, 8, "----", |cpu| cpu.dec_16(Register::DE)),
+    /* 1C */ Instruction::new("INC E", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::E)),
+    /* 1D */ Instruction::new("DEC E", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::E)),
+    /* 1E */ Instruction::new("LD E, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::E)),
+    /* 1F */ Instruction::new("RRA", Operand::None, 1, 4, 4, "000C", |cpu| cpu.rra()),
+    /* 20 */ Instruction::new("JR NZ, e8", Operand::Relative8, 2, 8, 12, "----", |cpu| cpu.jr_nz_imm1()),
+    /* 21 */ Instruction::new("LD HL, n16", Operand::Imm16, 3, 12, 12, "----", |cpu| cpu.ld_16_imm2(Register::HL)),
+    /* 22 */ Instruction::new("LD [HL+], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8_inc_dec(Register::HL, Register::A, true)),
+    /* 23 */ Instruction::new("INC HL", Operand::None, 1, 8, 8, "----", |cpu| cpu.inc_16(Register::HL)),
+    /* 24 */ Instruction::new("INC H", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::H)),
+    /* 25 */ Instruction::new("DEC H", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::H)),
+    /* 26 */ Instruction::new("LD H, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::H)),
+    /* 27 */ Instruction::new("DAA", Operand::None, 1, 4, 4, "Z-0C", |cpu| cpu.daa()),
+    /* 28 */ Instruction::new("JR Z, e8", Operand::Relative8, 2, 8, 12, "----", |cpu| cpu.jr_z_imm1()),
+    /* 29 */ Instruction::new("ADD HL, HL", Operand::None, 1, 8, 8, "-0HC", |cpu| cpu.add_16_16(Register::HL, Register::HL)),
+    /* 2A */ Instruction::new("LD A, [HL+]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16_inc_dec(Register::A, Register::HL, true)),
+    /* 2B */ Instruction::new("DEC HL", Operand::None, 1, 8, 8, "----", |cpu| cpu.dec_16(Register::HL)),
+    /* 2C */ Instruction::new("INC L", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::L)),
+    /* 2D */ Instruction::new("DEC L", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::L)),
+    /* 2E */ Instruction::new("LD L, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::L)),
+    /* 2F */ Instruction::new("CPL", Operand::None, 1, 4, 4, "-11-", |cpu| cpu.cpl()),
+    /* 30 */ Instruction::new("JR NC, e8", Operand::Relative8, 2, 8, 12, "----", |cpu| cpu.jr_nc_imm1()),
+    /* 31 */ Instruction::new("LD SP, n16", Operand::Imm16, 3, 12, 12, "----", |cpu| cpu.ld_16_imm2(Register::SP)),
+    /* 32 */ Instruction::new("LD [HL-], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8_inc_dec(Register::HL, Register::A, false)),
+    /* 33 */ Instruction::new("INC SP", Operand::None, 1, 8, 8, "----", |cpu| cpu.inc_16(Register::SP)),
+    /* 34 */ Instruction::new("INC [HL]", Operand::None, 1, 12, 12, "Z0H-", |cpu| cpu.inc_mem16(Register::HL)),
+    /* 35 */ Instruction::new("DEC [HL]", Operand::None, 1, 12, 12, "Z1H-", |cpu| cpu.dec_mem16(Register::HL)),
+    /* 36 */ Instruction::new("LD [HL], n8", Operand::Imm8, 2, 12, 12, "----", |cpu| cpu.ld_mem16_imm1(Register::HL)),
+    /* 37 */ Instruction::new("SCF", Operand::None, 1, 4, 4, "-001", |cpu| cpu.scf()),
+    /* 38 */ Instruction::new("JR C, e8", Operand::Relative8, 2, 8, 12, "----", |cpu| cpu.jr_c_imm1()),
+    /* 39 */ Instruction::new("ADD HL, SP", Operand::None, 1, 8, 8, "-0HC", |cpu| cpu.add_16_16(Register::HL, Register::SP)),
+    /* 3A */ Instruction::new("LD A, [HL-]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16_inc_dec(Register::A, Register::HL, false)),
+    /* 3B */ Instruction::new("DEC SP", Operand::None, 1, 8, 8, "----", |cpu| cpu.dec_16(Register::SP)),
+    /* 3C */ Instruction::new("INC A", Operand::None, 1, 4, 4, "Z0H-", |cpu| cpu.inc_8(Register::A)),
+    /* 3D */ Instruction::new("DEC A", Operand::None, 1, 4, 4, "Z1H-", |cpu| cpu.dec_8(Register::A)),
+    /* 3E */ Instruction::new("LD A, n8", Operand::Imm8, 2, 8, 8, "----", |cpu| cpu.ld_8_imm1(Register::A)),
+    /* 3F */ Instruction::new("CCF", Operand::None, 1, 4, 4, "-00C", |cpu| cpu.ccf()),
+    /* 40 */ Instruction::new("LD B, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::B)),
+    /* 41 */ Instruction::new("LD B, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::C)),
+    /* 42 */ Instruction::new("LD B, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::D)),
+    /* 43 */ Instruction::new("LD B, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::E)),
+    /* 44 */ Instruction::new("LD B, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::H)),
+    /* 45 */ Instruction::new("LD B, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::L)),
+    /* 46 */ Instruction::new("LD B, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::B, Register::HL)),
+    /* 47 */ Instruction::new("LD B, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::B, Register::A)),
+    /* 48 */ Instruction::new("LD C, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::B)),
+    /* 49 */ Instruction::new("LD C, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::C)),
+    /* 4A */ Instruction::new("LD C, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::D)),
+    /* 4B */ Instruction::new("LD C, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::E)),
+    /* 4C */ Instruction::new("LD C, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::H)),
+    /* 4D */ Instruction::new("LD C, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::L)),
+    /* 4E */ Instruction::new("LD C, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::C, Register::HL)),
+    /* 4F */ Instruction::new("LD C, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::C, Register::A)),
+    /* 50 */ Instruction::new("LD D, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::B)),
+    /* 51 */ Instruction::new("LD D, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::C)),
+    /* 52 */ Instruction::new("LD D, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::D)),
+    /* 53 */ Instruction::new("LD D, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::E)),
+    /* 54 */ Instruction::new("LD D, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::H)),
+    /* 55 */ Instruction::new("LD D, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::L)),
+    /* 56 */ Instruction::new("LD D, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::D, Register::HL)),
+    /* 57 */ Instruction::new("LD D, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::D, Register::A)),
+    /* 58 */ Instruction::new("LD E, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::B)),
+    /* 59 */ Instruction::new("LD E, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::C)),
+    /* 5A */ Instruction::new("LD E, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::D)),
+    /* 5B */ Instruction::new("LD E, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::E)),
+    /* 5C */ Instruction::new("LD E, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::H)),
+    /* 5D */ Instruction::new("LD E, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::L)),
+    /* 5E */ Instruction::new("LD E, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::E, Register::HL)),
+    /* 5F */ Instruction::new("LD E, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::E, Register::A)),
+    /* 60 */ Instruction::new("LD H, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::B)),
+    /* 61 */ Instruction::new("LD H, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::C)),
+    /* 62 */ Instruction::new("LD H, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::D)),
+    /* 63 */ Instruction::new("LD H, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::E)),
+    /* 64 */ Instruction::new("LD H, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::H)),
+    /* 65 */ Instruction::new("LD H, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::L)),
+    /* 66 */ Instruction::new("LD H, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::H, Register::HL)),
+    /* 67 */ Instruction::new("LD H, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::H, Register::A)),
+    /* 68 */ Instruction::new("LD L, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::B)),
+    /* 69 */ Instruction::new("LD L, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::C)),
+    /* 6A */ Instruction::new("LD L, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::D)),
+    /* 6B */ Instruction::new("LD L, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::E)),
+    /* 6C */ Instruction::new("LD L, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::H)),
+    /* 6D */ Instruction::new("LD L, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::L)),
+    /* 6E */ Instruction::new("LD L, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::L, Register::HL)),
+    /* 6F */ Instruction::new("LD L, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::L, Register::A)),
+    /* 70 */ Instruction::new("LD [HL], B", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::B)),
+    /* 71 */ Instruction::new("LD [HL], C", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::C)),
+    /* 72 */ Instruction::new("LD [HL], D", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::D)),
+    /* 73 */ Instruction::new("LD [HL], E", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::E)),
+    /* 74 */ Instruction::new("LD [HL], H", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::H)),
+    /* 75 */ Instruction::new("LD [HL], L", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::L)),
+    /* 76 */ Instruction::new("HALT", Operand::None, 1, 4, 4, "----", |cpu| cpu.halt()),
+    /* 77 */ Instruction::new("LD [HL], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem16_8(Register::HL, Register::A)),
+    /* 78 */ Instruction::new("LD A, B", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::B)),
+    /* 79 */ Instruction::new("LD A, C", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::C)),
+    /* 7A */ Instruction::new("LD A, D", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::D)),
+    /* 7B */ Instruction::new("LD A, E", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::E)),
+    /* 7C */ Instruction::new("LD A, H", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::H)),
+    /* 7D */ Instruction::new("LD A, L", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::L)),
+    /* 7E */ Instruction::new("LD A, [HL]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem16(Register::A, Register::HL)),
+    /* 7F */ Instruction::new("LD A, A", Operand::None, 1, 4, 4, "----", |cpu| cpu.ld_8_8(Register::A, Register::A)),
+    /* 80 */ Instruction::new("ADD A, B", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::B)),
+    /* 81 */ Instruction::new("ADD A, C", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::C)),
+    /* 82 */ Instruction::new("ADD A, D", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::D)),
+    /* 83 */ Instruction::new("ADD A, E", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::E)),
+    /* 84 */ Instruction::new("ADD A, H", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::H)),
+    /* 85 */ Instruction::new("ADD A, L", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::L)),
+    /* 86 */ Instruction::new("ADD A, [HL]", Operand::None, 1, 8, 8, "Z0HC", |cpu| cpu.add_8_mem16(Register::A, Register::HL)),
+    /* 87 */ Instruction::new("ADD A, A", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.add_8_8(Register::A, Register::A)),
+    /* 88 */ Instruction::new("ADC A, B", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::B)),
+    /* 89 */ Instruction::new("ADC A, C", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::C)),
+    /* 8A */ Instruction::new("ADC A, D", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::D)),
+    /* 8B */ Instruction::new("ADC A, E", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::E)),
+    /* 8C */ Instruction::new("ADC A, H", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::H)),
+    /* 8D */ Instruction::new("ADC A, L", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::L)),
+    /* 8E */ Instruction::new("ADC A, [HL]", Operand::None, 1, 8, 8, "Z0HC", |cpu| cpu.adc_8_mem16(Register::A, Register::HL)),
+    /* 8F */ Instruction::new("ADC A, A", Operand::None, 1, 4, 4, "Z0HC", |cpu| cpu.adc_8_8(Register::A, Register::A)),
+    /* 90 */ Instruction::new("SUB A, B", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::B)),
+    /* 91 */ Instruction::new("SUB A, C", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::C)),
+    /* 92 */ Instruction::new("SUB A, D", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::D)),
+    /* 93 */ Instruction::new("SUB A, E", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::E)),
+    /* 94 */ Instruction::new("SUB A, H", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::H)),
+    /* 95 */ Instruction::new("SUB A, L", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::L)),
+    /* 96 */ Instruction::new("SUB A, [HL]", Operand::None, 1, 8, 8, "Z1HC", |cpu| cpu.sub_8_mem16(Register::A, Register::HL)),
+    /* 97 */ Instruction::new("SUB A, A", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sub_8_8(Register::A, Register::A)),
+    /* 98 */ Instruction::new("SBC A, B", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::B)),
+    /* 99 */ Instruction::new("SBC A, C", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::C)),
+    /* 9A */ Instruction::new("SBC A, D", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::D)),
+    /* 9B */ Instruction::new("SBC A, E", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::E)),
+    /* 9C */ Instruction::new("SBC A, H", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::H)),
+    /* 9D */ Instruction::new("SBC A, L", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::L)),
+    /* 9E */ Instruction::new("SBC A, [HL]", Operand::None, 1, 8, 8, "Z1HC", |cpu| cpu.sbc_8_mem16(Register::A, Register::HL)),
+    /* 9F */ Instruction::new("SBC A, A", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.sbc_8_8(Register::A, Register::A)),
+    /* A0 */ Instruction::new("AND A, B", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::B)),
+    /* A1 */ Instruction::new("AND A, C", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::C)),
+    /* A2 */ Instruction::new("AND A, D", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::D)),
+    /* A3 */ Instruction::new("AND A, E", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::E)),
+    /* A4 */ Instruction::new("AND A, H", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::H)),
+    /* A5 */ Instruction::new("AND A, L", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::L)),
+    /* A6 */ Instruction::new("AND A, [HL]", Operand::None, 1, 8, 8, "Z010", |cpu| cpu.and_8_mem16(Register::A, Register::HL)),
+    /* A7 */ Instruction::new("AND A, A", Operand::None, 1, 4, 4, "Z010", |cpu| cpu.and_8_8(Register::A, Register::A)),
+    /* A8 */ Instruction::new("XOR A, B", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::B)),
+    /* A9 */ Instruction::new("XOR A, C", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::C)),
+    /* AA */ Instruction::new("XOR A, D", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::D)),
+    /* AB */ Instruction::new("XOR A, E", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::E)),
+    /* AC */ Instruction::new("XOR A, H", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::H)),
+    /* AD */ Instruction::new("XOR A, L", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::L)),
+    /* AE */ Instruction::new("XOR A, [HL]", Operand::None, 1, 8, 8, "Z000", |cpu| cpu.xor_8_mem16(Register::A, Register::HL)),
+    /* AF */ Instruction::new("XOR A, A", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.xor_8_8(Register::A, Register::A)),
+    /* B0 */ Instruction::new("OR A, B", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::B)),
+    /* B1 */ Instruction::new("OR A, C", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::C)),
+    /* B2 */ Instruction::new("OR A, D", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::D)),
+    /* B3 */ Instruction::new("OR A, E", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::E)),
+    /* B4 */ Instruction::new("OR A, H", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::H)),
+    /* B5 */ Instruction::new("OR A, L", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::L)),
+    /* B6 */ Instruction::new("OR A, [HL]", Operand::None, 1, 8, 8, "Z000", |cpu| cpu.or_8_mem16(Register::A, Register::HL)),
+    /* B7 */ Instruction::new("OR A, A", Operand::None, 1, 4, 4, "Z000", |cpu| cpu.or_8_8(Register::A, Register::A)),
+    /* B8 */ Instruction::new("CP A, B", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::B)),
+    /* B9 */ Instruction::new("CP A, C", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::C)),
+    /* BA */ Instruction::new("CP A, D", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::D)),
+    /* BB */ Instruction::new("CP A, E", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::E)),
+    /* BC */ Instruction::new("CP A, H", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::H)),
+    /* BD */ Instruction::new("CP A, L", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::L)),
+    /* BE */ Instruction::new("CP A, [HL]", Operand::None, 1, 8, 8, "Z1HC", |cpu| cpu.cp_8_mem16(Register::A, Register::HL)),
+    /* BF */ Instruction::new("CP A, A", Operand::None, 1, 4, 4, "Z1HC", |cpu| cpu.cp_8_8(Register::A, Register::A)),
+    /* C0 */ Instruction::new("RET NZ", Operand::None, 1, 8, 20, "----", |cpu| cpu.ret_nz()),
+    /* C1 */ Instruction::new("POP BC", Operand::None, 1, 12, 12, "----", |cpu| cpu.pop_16(Register::BC)),
+    /* C2 */ Instruction::new("JP NZ, a16", Operand::Addr16, 3, 12, 16, "----", |cpu| cpu.jp_nz_imm2()),
+    /* C3 */ Instruction::new("JP a16", Operand::Addr16, 3, 16, 16, "----", |cpu| cpu.jp_imm2()),
+    /* C4 */ Instruction::new("CALL NZ, a16", Operand::Addr16, 3, 12, 24, "----", |cpu| cpu.call_nz_imm2()),
+    /* C5 */ Instruction::new("PUSH BC", Operand::None, 1, 16, 16, "----", |cpu| cpu.push_16(Register::BC)),
+    /* C6 */ Instruction::new("ADD A, n8", Operand::Imm8, 2, 8, 8, "Z0HC", |cpu| cpu.add_8_imm1(Register::A)),
+    /* C7 */ Instruction::new("RST $00", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x00)),
+    /* C8 */ Instruction::new("RET Z", Operand::None, 1, 8, 20, "----", |cpu| cpu.ret_z()),
+    /* C9 */ Instruction::new("RET", Operand::None, 1, 16, 16, "----", |cpu| cpu.ret()),
+    /* CA */ Instruction::new("JP Z, a16", Operand::Addr16, 3, 12, 16, "----", |cpu| cpu.jp_z_imm2()),
+    /* CB */ Instruction::new("PREFIX", Operand::None, 1, 4, 4, "----", |cpu| cpu.prefix_cb()),
+    /* CC */ Instruction::new("CALL Z, a16", Operand::Addr16, 3, 12, 24, "----", |cpu| cpu.call_z_imm2()),
+    /* CD */ Instruction::new("CALL a16", Operand::Addr16, 3, 24, 24, "----", |cpu| cpu.call_imm2()),
+    /* CE */ Instruction::new("ADC A, n8", Operand::Imm8, 2, 8, 8, "Z0HC", |cpu| cpu.adc_8_imm1(Register::A)),
+    /* CF */ Instruction::new("RST $08", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x08)),
+    /* D0 */ Instruction::new("RET NC", Operand::None, 1, 8, 20, "----", |cpu| cpu.ret_nc()),
+    /* D1 */ Instruction::new("POP DE", Operand::None, 1, 12, 12, "----", |cpu| cpu.pop_16(Register::DE)),
+    /* D2 */ Instruction::new("JP NC, a16", Operand::Addr16, 3, 12, 16, "----", |cpu| cpu.jp_nc_imm2()),
+    /* D3 */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xD3)),
+    /* D4 */ Instruction::new("CALL NC, a16", Operand::Addr16, 3, 12, 24, "----", |cpu| cpu.call_nc_imm2()),
+    /* D5 */ Instruction::new("PUSH DE", Operand::None, 1, 16, 16, "----", |cpu| cpu.push_16(Register::DE)),
+    /* D6 */ Instruction::new("SUB A, n8", Operand::Imm8, 2, 8, 8, "Z1HC", |cpu| cpu.sub_8_imm1(Register::A)),
+    /* D7 */ Instruction::new("RST $10", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x10)),
+    /* D8 */ Instruction::new("RET C", Operand::None, 1, 8, 20, "----", |cpu| cpu.ret_c()),
+    /* D9 */ Instruction::new("RETI", Operand::None, 1, 16, 16, "----", |cpu| cpu.reti()),
+    /* DA */ Instruction::new("JP C, a16", Operand::Addr16, 3, 12, 16, "----", |cpu| cpu.jp_c_imm2()),
+    /* DB */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xDB)),
+    /* DC */ Instruction::new("CALL C, a16", Operand::Addr16, 3, 12, 24, "----", |cpu| cpu.call_c_imm2()),
+    /* DD */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xDD)),
+    /* DE */ Instruction::new("SBC A, n8", Operand::Imm8, 2, 8, 8, "Z1HC", |cpu| cpu.sbc_8_imm1(Register::A)),
+    /* DF */ Instruction::new("RST $18", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x18)),
+    /* E0 */ Instruction::new("LDH [a8], A", Operand::HighAddr8, 2, 12, 12, "----", |cpu| cpu.ldh_memimm1_8(Register::A)),
+    /* E1 */ Instruction::new("POP HL", Operand::None, 1, 12, 12, "----", |cpu| cpu.pop_16(Register::HL)),
+    /* E2 */ Instruction::new("LDH [C], A", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_mem8_8(Register::C, Register::A)),
+    /* E3 */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xE3)),
+    /* E4 */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xE4)),
+    /* E5 */ Instruction::new("PUSH HL", Operand::None, 1, 16, 16, "----", |cpu| cpu.push_16(Register::HL)),
+    /* E6 */ Instruction::new("AND A, n8", Operand::Imm8, 2, 8, 8, "Z010", |cpu| cpu.and_8_imm1(Register::A)),
+    /* E7 */ Instruction::new("RST $20", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x20)),
+    /* E8 */ Instruction::new("ADD SP, e8", Operand::Offset8, 2, 16, 16, "00HC", |cpu| cpu.add_16_imm1(Register::SP)),
+    /* E9 */ Instruction::new("JP HL", Operand::None, 1, 4, 4, "----", |cpu| cpu.jp_mem16(Register::HL)),
+    /* EA */ Instruction::new("LD [a16], A", Operand::Addr16, 3, 16, 16, "----", |cpu| cpu.ld_memimm2_8(Register::A)),
+    /* EB */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xEB)),
+    /* EC */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xEC)),
+    /* ED */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xED)),
+    /* EE */ Instruction::new("XOR A, n8", Operand::Imm8, 2, 8, 8, "Z000", |cpu| cpu.xor_8_imm1(Register::A)),
+    /* EF */ Instruction::new("RST $28", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x28)),
+    /* F0 */ Instruction::new("LDH A, [a8]", Operand::HighAddr8, 2, 12, 12, "----", |cpu| cpu.ldh_8_memimm1(Register::A)),
+    /* F1 */ Instruction::new("POP AF", Operand::None, 1, 12, 12, "ZNHC", |cpu| cpu.pop_16(Register::AF)),
+    /* F2 */ Instruction::new("LDH A, [C]", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_8_mem8(Register::A, Register::C)),
+    /* F3 */ Instruction::new("DI", Operand::None, 1, 4, 4, "----", |cpu| cpu.di()),
+    /* F4 */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xF4)),
+    /* F5 */ Instruction::new("PUSH AF", Operand::None, 1, 16, 16, "----", |cpu| cpu.push_16(Register::AF)),
+    /* F6 */ Instruction::new("OR A, n8", Operand::Imm8, 2, 8, 8, "Z000", |cpu| cpu.or_8_imm1(Register::A)),
+    /* F7 */ Instruction::new("RST $30", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x30)),
+    /* F8 */ Instruction::new("LD HL, SP + e8", Operand::Offset8, 2, 12, 12, "00HC", |cpu| cpu.ld_hl_sp_r8()),
+    /* F9 */ Instruction::new("LD SP, HL", Operand::None, 1, 8, 8, "----", |cpu| cpu.ld_16_16(Register::SP, Register::HL)),
+    /* FA */ Instruction::new("LD A, [a16]", Operand::Addr16, 3, 16, 16, "----", |cpu| cpu.ld_8_memimm2(Register::A)),
+    /* FB */ Instruction::new("EI", Operand::None, 1, 4, 4, "----", |cpu| cpu.ei()),
+    /* FC */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xFC)),
+    /* FD */ Instruction::new("ILLEGAL", Operand::None, 1, 4, 4, "----", |cpu| cpu.illegal(0xFD)),
+    /* FE */ Instruction::new("CP A, n8", Operand::Imm8, 2, 8, 8, "Z1HC", |cpu| cpu.cp_8_imm1(Register::A)),
+    /* FF */ Instruction::new("RST $38", Operand::None, 1, 16, 16, "----", |cpu| cpu.rst(0x38)),
+];
+
+#[rustfmt::skip]
+pub static CB_PREFIXED: [Instruction; 256] = [
+    /* 00 */ Instruction::new("RLC B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::B)),
+    /* 01 */ Instruction::new("RLC C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::C)),
+    /* 02 */ Instruction::new("RLC D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::D)),
+    /* 03 */ Instruction::new("RLC E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::E)),
+    /* 04 */ Instruction::new("RLC H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::H)),
+    /* 05 */ Instruction::new("RLC L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::L)),
+    /* 06 */ Instruction::new("RLC [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.rlc_mem16(Register::HL)),
+    /* 07 */ Instruction::new("RLC A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rlc_8(Register::A)),
+    /* 08 */ Instruction::new("RRC B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::B)),
+    /* 09 */ Instruction::new("RRC C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::C)),
+    /* 0A */ Instruction::new("RRC D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::D)),
+    /* 0B */ Instruction::new("RRC E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::E)),
+    /* 0C */ Instruction::new("RRC H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::H)),
+    /* 0D */ Instruction::new("RRC L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::L)),
+    /* 0E */ Instruction::new("RRC [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.rrc_mem16(Register::HL)),
+    /* 0F */ Instruction::new("RRC A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rrc_8(Register::A)),
+    /* 10 */ Instruction::new("RL B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::B)),
+    /* 11 */ Instruction::new("RL C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::C)),
+    /* 12 */ Instruction::new("RL D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::D)),
+    /* 13 */ Instruction::new("RL E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::E)),
+    /* 14 */ Instruction::new("RL H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::H)),
+    /* 15 */ Instruction::new("RL L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::L)),
+    /* 16 */ Instruction::new("RL [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.rl_mem16(Register::HL)),
+    /* 17 */ Instruction::new("RL A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rl_8(Register::A)),
+    /* 18 */ Instruction::new("RR B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::B)),
+    /* 19 */ Instruction::new("RR C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::C)),
+    /* 1A */ Instruction::new("RR D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::D)),
+    /* 1B */ Instruction::new("RR E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::E)),
+    /* 1C */ Instruction::new("RR H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::H)),
+    /* 1D */ Instruction::new("RR L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::L)),
+    /* 1E */ Instruction::new("RR [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.rr_mem16(Register::HL)),
+    /* 1F */ Instruction::new("RR A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.rr_8(Register::A)),
+    /* 20 */ Instruction::new("SLA B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::B)),
+    /* 21 */ Instruction::new("SLA C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::C)),
+    /* 22 */ Instruction::new("SLA D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::D)),
+    /* 23 */ Instruction::new("SLA E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::E)),
+    /* 24 */ Instruction::new("SLA H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::H)),
+    /* 25 */ Instruction::new("SLA L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::L)),
+    /* 26 */ Instruction::new("SLA [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.sla_mem16(Register::HL)),
+    /* 27 */ Instruction::new("SLA A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sla_8(Register::A)),
+    /* 28 */ Instruction::new("SRA B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::B)),
+    /* 29 */ Instruction::new("SRA C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::C)),
+    /* 2A */ Instruction::new("SRA D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::D)),
+    /* 2B */ Instruction::new("SRA E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::E)),
+    /* 2C */ Instruction::new("SRA H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::H)),
+    /* 2D */ Instruction::new("SRA L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::L)),
+    /* 2E */ Instruction::new("SRA [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.sra_mem16(Register::HL)),
+    /* 2F */ Instruction::new("SRA A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.sra_8(Register::A)),
+    /* 30 */ Instruction::new("SWAP B", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::B)),
+    /* 31 */ Instruction::new("SWAP C", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::C)),
+    /* 32 */ Instruction::new("SWAP D", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::D)),
+    /* 33 */ Instruction::new("SWAP E", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::E)),
+    /* 34 */ Instruction::new("SWAP H", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::H)),
+    /* 35 */ Instruction::new("SWAP L", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::L)),
+    /* 36 */ Instruction::new("SWAP [HL]", Operand::None, 2, 16, 16, "Z000", |cpu| cpu.swap_mem16(Register::HL)),
+    /* 37 */ Instruction::new("SWAP A", Operand::None, 2, 8, 8, "Z000", |cpu| cpu.swap_8(Register::A)),
+    /* 38 */ Instruction::new("SRL B", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::B)),
+    /* 39 */ Instruction::new("SRL C", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::C)),
+    /* 3A */ Instruction::new("SRL D", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::D)),
+    /* 3B */ Instruction::new("SRL E", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::E)),
+    /* 3C */ Instruction::new("SRL H", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::H)),
+    /* 3D */ Instruction::new("SRL L", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::L)),
+    /* 3E */ Instruction::new("SRL [HL]", Operand::None, 2, 16, 16, "Z00C", |cpu| cpu.srl_mem16(Register::HL)),
+    /* 3F */ Instruction::new("SRL A", Operand::None, 2, 8, 8, "Z00C", |cpu| cpu.srl_8(Register::A)),
+    /* 40 */ Instruction::new("BIT 0, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::B)),
+    /* 41 */ Instruction::new("BIT 0, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::C)),
+    /* 42 */ Instruction::new("BIT 0, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::D)),
+    /* 43 */ Instruction::new("BIT 0, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::E)),
+    /* 44 */ Instruction::new("BIT 0, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::H)),
+    /* 45 */ Instruction::new("BIT 0, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::L)),
+    /* 46 */ Instruction::new("BIT 0, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(0, Register::HL)),
+    /* 47 */ Instruction::new("BIT 0, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(0, Register::A)),
+    /* 48 */ Instruction::new("BIT 1, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::B)),
+    /* 49 */ Instruction::new("BIT 1, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::C)),
+    /* 4A */ Instruction::new("BIT 1, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::D)),
+    /* 4B */ Instruction::new("BIT 1, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::E)),
+    /* 4C */ Instruction::new("BIT 1, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::H)),
+    /* 4D */ Instruction::new("BIT 1, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::L)),
+    /* 4E */ Instruction::new("BIT 1, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(1, Register::HL)),
+    /* 4F */ Instruction::new("BIT 1, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(1, Register::A)),
+    /* 50 */ Instruction::new("BIT 2, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::B)),
+    /* 51 */ Instruction::new("BIT 2, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::C)),
+    /* 52 */ Instruction::new("BIT 2, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::D)),
+    /* 53 */ Instruction::new("BIT 2, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::E)),
+    /* 54 */ Instruction::new("BIT 2, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::H)),
+    /* 55 */ Instruction::new("BIT 2, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::L)),
+    /* 56 */ Instruction::new("BIT 2, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(2, Register::HL)),
+    /* 57 */ Instruction::new("BIT 2, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(2, Register::A)),
+    /* 58 */ Instruction::new("BIT 3, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::B)),
+    /* 59 */ Instruction::new("BIT 3, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::C)),
+    /* 5A */ Instruction::new("BIT 3, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::D)),
+    /* 5B */ Instruction::new("BIT 3, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::E)),
+    /* 5C */ Instruction::new("BIT 3, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::H)),
+    /* 5D */ Instruction::new("BIT 3, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::L)),
+    /* 5E */ Instruction::new("BIT 3, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(3, Register::HL)),
+    /* 5F */ Instruction::new("BIT 3, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(3, Register::A)),
+    /* 60 */ Instruction::new("BIT 4, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::B)),
+    /* 61 */ Instruction::new("BIT 4, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::C)),
+    /* 62 */ Instruction::new("BIT 4, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::D)),
+    /* 63 */ Instruction::new("BIT 4, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::E)),
+    /* 64 */ Instruction::new("BIT 4, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::H)),
+    /* 65 */ Instruction::new("BIT 4, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::L)),
+    /* 66 */ Instruction::new("BIT 4, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(4, Register::HL)),
+    /* 67 */ Instruction::new("BIT 4, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(4, Register::A)),
+    /* 68 */ Instruction::new("BIT 5, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::B)),
+    /* 69 */ Instruction::new("BIT 5, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::C)),
+    /* 6A */ Instruction::new("BIT 5, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::D)),
+    /* 6B */ Instruction::new("BIT 5, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::E)),
+    /* 6C */ Instruction::new("BIT 5, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::H)),
+    /* 6D */ Instruction::new("BIT 5, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::L)),
+    /* 6E */ Instruction::new("BIT 5, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(5, Register::HL)),
+    /* 6F */ Instruction::new("BIT 5, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(5, Register::A)),
+    /* 70 */ Instruction::new("BIT 6, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::B)),
+    /* 71 */ Instruction::new("BIT 6, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::C)),
+    /* 72 */ Instruction::new("BIT 6, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::D)),
+    /* 73 */ Instruction::new("BIT 6, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::E)),
+    /* 74 */ Instruction::new("BIT 6, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::H)),
+    /* 75 */ Instruction::new("BIT 6, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::L)),
+    /* 76 */ Instruction::new("BIT 6, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(6, Register::HL)),
+    /* 77 */ Instruction::new("BIT 6, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(6, Register::A)),
+    /* 78 */ Instruction::new("BIT 7, B", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::B)),
+    /* 79 */ Instruction::new("BIT 7, C", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::C)),
+    /* 7A */ Instruction::new("BIT 7, D", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::D)),
+    /* 7B */ Instruction::new("BIT 7, E", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::E)),
+    /* 7C */ Instruction::new("BIT 7, H", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::H)),
+    /* 7D */ Instruction::new("BIT 7, L", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::L)),
+    /* 7E */ Instruction::new("BIT 7, [HL]", Operand::None, 2, 12, 12, "Z01-", |cpu| cpu.bit_mem16(7, Register::HL)),
+    /* 7F */ Instruction::new("BIT 7, A", Operand::None, 2, 8, 8, "Z01-", |cpu| cpu.bit_8(7, Register::A)),
+    /* 80 */ Instruction::new("RES 0, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::B)),
+    /* 81 */ Instruction::new("RES 0, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::C)),
+    /* 82 */ Instruction::new("RES 0, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::D)),
+    /* 83 */ Instruction::new("RES 0, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::E)),
+    /* 84 */ Instruction::new("RES 0, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::H)),
+    /* 85 */ Instruction::new("RES 0, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::L)),
+    /* 86 */ Instruction::new("RES 0, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(0, Register::HL)),
+    /* 87 */ Instruction::new("RES 0, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(0, Register::A)),
+    /* 88 */ Instruction::new("RES 1, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::B)),
+    /* 89 */ Instruction::new("RES 1, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::C)),
+    /* 8A */ Instruction::new("RES 1, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::D)),
+    /* 8B */ Instruction::new("RES 1, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::E)),
+    /* 8C */ Instruction::new("RES 1, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::H)),
+    /* 8D */ Instruction::new("RES 1, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::L)),
+    /* 8E */ Instruction::new("RES 1, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(1, Register::HL)),
+    /* 8F */ Instruction::new("RES 1, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(1, Register::A)),
+    /* 90 */ Instruction::new("RES 2, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::B)),
+    /* 91 */ Instruction::new("RES 2, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::C)),
+    /* 92 */ Instruction::new("RES 2, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::D)),
+    /* 93 */ Instruction::new("RES 2, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::E)),
+    /* 94 */ Instruction::new("RES 2, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::H)),
+    /* 95 */ Instruction::new("RES 2, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::L)),
+    /* 96 */ Instruction::new("RES 2, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(2, Register::HL)),
+    /* 97 */ Instruction::new("RES 2, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(2, Register::A)),
+    /* 98 */ Instruction::new("RES 3, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::B)),
+    /* 99 */ Instruction::new("RES 3, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::C)),
+    /* 9A */ Instruction::new("RES 3, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::D)),
+    /* 9B */ Instruction::new("RES 3, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::E)),
+    /* 9C */ Instruction::new("RES 3, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::H)),
+    /* 9D */ Instruction::new("RES 3, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::L)),
+    /* 9E */ Instruction::new("RES 3, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(3, Register::HL)),
+    /* 9F */ Instruction::new("RES 3, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(3, Register::A)),
+    /* A0 */ Instruction::new("RES 4, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::B)),
+    /* A1 */ Instruction::new("RES 4, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::C)),
+    /* A2 */ Instruction::new("RES 4, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::D)),
+    /* A3 */ Instruction::new("RES 4, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::E)),
+    /* A4 */ Instruction::new("RES 4, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::H)),
+    /* A5 */ Instruction::new("RES 4, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::L)),
+    /* A6 */ Instruction::new("RES 4, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(4, Register::HL)),
+    /* A7 */ Instruction::new("RES 4, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(4, Register::A)),
+    /* A8 */ Instruction::new("RES 5, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::B)),
+    /* A9 */ Instruction::new("RES 5, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::C)),
+    /* AA */ Instruction::new("RES 5, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::D)),
+    /* AB */ Instruction::new("RES 5, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::E)),
+    /* AC */ Instruction::new("RES 5, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::H)),
+    /* AD */ Instruction::new("RES 5, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::L)),
+    /* AE */ Instruction::new("RES 5, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(5, Register::HL)),
+    /* AF */ Instruction::new("RES 5, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(5, Register::A)),
+    /* B0 */ Instruction::new("RES 6, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::B)),
+    /* B1 */ Instruction::new("RES 6, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::C)),
+    /* B2 */ Instruction::new("RES 6, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::D)),
+    /* B3 */ Instruction::new("RES 6, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::E)),
+    /* B4 */ Instruction::new("RES 6, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::H)),
+    /* B5 */ Instruction::new("RES 6, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::L)),
+    /* B6 */ Instruction::new("RES 6, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(6, Register::HL)),
+    /* B7 */ Instruction::new("RES 6, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(6, Register::A)),
+    /* B8 */ Instruction::new("RES 7, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::B)),
+    /* B9 */ Instruction::new("RES 7, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::C)),
+    /* BA */ Instruction::new("RES 7, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::D)),
+    /* BB */ Instruction::new("RES 7, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::E)),
+    /* BC */ Instruction::new("RES 7, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::H)),
+    /* BD */ Instruction::new("RES 7, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::L)),
+    /* BE */ Instruction::new("RES 7, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.res_mem16(7, Register::HL)),
+    /* BF */ Instruction::new("RES 7, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.res_8(7, Register::A)),
+    /* C0 */ Instruction::new("SET 0, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 0)),
+    /* C1 */ Instruction::new("SET 0, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 0)),
+    /* C2 */ Instruction::new("SET 0, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 0)),
+    /* C3 */ Instruction::new("SET 0, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 0)),
+    /* C4 */ Instruction::new("SET 0, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 0)),
+    /* C5 */ Instruction::new("SET 0, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 0)),
+    /* C6 */ Instruction::new("SET 0, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 0)),
+    /* C7 */ Instruction::new("SET 0, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 0)),
+    /* C8 */ Instruction::new("SET 1, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 1)),
+    /* C9 */ Instruction::new("SET 1, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 1)),
+    /* CA */ Instruction::new("SET 1, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 1)),
+    /* CB */ Instruction::new("SET 1, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 1)),
+    /* CC */ Instruction::new("SET 1, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 1)),
+    /* CD */ Instruction::new("SET 1, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 1)),
+    /* CE */ Instruction::new("SET 1, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 1)),
+    /* CF */ Instruction::new("SET 1, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 1)),
+    /* D0 */ Instruction::new("SET 2, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 2)),
+    /* D1 */ Instruction::new("SET 2, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 2)),
+    /* D2 */ Instruction::new("SET 2, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 2)),
+    /* D3 */ Instruction::new("SET 2, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 2)),
+    /* D4 */ Instruction::new("SET 2, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 2)),
+    /* D5 */ Instruction::new("SET 2, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 2)),
+    /* D6 */ Instruction::new("SET 2, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 2)),
+    /* D7 */ Instruction::new("SET 2, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 2)),
+    /* D8 */ Instruction::new("SET 3, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 3)),
+    /* D9 */ Instruction::new("SET 3, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 3)),
+    /* DA */ Instruction::new("SET 3, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 3)),
+    /* DB */ Instruction::new("SET 3, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 3)),
+    /* DC */ Instruction::new("SET 3, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 3)),
+    /* DD */ Instruction::new("SET 3, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 3)),
+    /* DE */ Instruction::new("SET 3, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 3)),
+    /* DF */ Instruction::new("SET 3, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 3)),
+    /* E0 */ Instruction::new("SET 4, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 4)),
+    /* E1 */ Instruction::new("SET 4, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 4)),
+    /* E2 */ Instruction::new("SET 4, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 4)),
+    /* E3 */ Instruction::new("SET 4, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 4)),
+    /* E4 */ Instruction::new("SET 4, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 4)),
+    /* E5 */ Instruction::new("SET 4, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 4)),
+    /* E6 */ Instruction::new("SET 4, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 4)),
+    /* E7 */ Instruction::new("SET 4, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 4)),
+    /* E8 */ Instruction::new("SET 5, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 5)),
+    /* E9 */ Instruction::new("SET 5, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 5)),
+    /* EA */ Instruction::new("SET 5, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 5)),
+    /* EB */ Instruction::new("SET 5, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 5)),
+    /* EC */ Instruction::new("SET 5, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 5)),
+    /* ED */ Instruction::new("SET 5, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 5)),
+    /* EE */ Instruction::new("SET 5, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 5)),
+    /* EF */ Instruction::new("SET 5, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 5)),
+    /* F0 */ Instruction::new("SET 6, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 6)),
+    /* F1 */ Instruction::new("SET 6, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 6)),
+    /* F2 */ Instruction::new("SET 6, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 6)),
+    /* F3 */ Instruction::new("SET 6, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 6)),
+    /* F4 */ Instruction::new("SET 6, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 6)),
+    /* F5 */ Instruction::new("SET 6, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 6)),
+    /* F6 */ Instruction::new("SET 6, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 6)),
+    /* F7 */ Instruction::new("SET 6, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 6)),
+    /* F8 */ Instruction::new("SET 7, B", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::B, 7)),
+    /* F9 */ Instruction::new("SET 7, C", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::C, 7)),
+    /* FA */ Instruction::new("SET 7, D", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::D, 7)),
+    /* FB */ Instruction::new("SET 7, E", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::E, 7)),
+    /* FC */ Instruction::new("SET 7, H", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::H, 7)),
+    /* FD */ Instruction::new("SET 7, L", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::L, 7)),
+    /* FE */ Instruction::new("SET 7, [HL]", Operand::None, 2, 16, 16, "----", |cpu| cpu.set_mem16(Register::HL, 7)),
+    /* FF */ Instruction::new("SET 7, A", Operand::None, 2, 8, 8, "----", |cpu| cpu.set_8(Register::A, 7)),
+];
@@ -1,11 +1,14 @@
 use crate::device::SharedMemory;
 
+pub const SCREEN_WIDTH: usize = 160;
+pub const SCREEN_HEIGHT: usize = 144;
+
 pub struct PPU {
     pub memory: SharedMemory,
     pub mode: u8,
     pub mode_clock: u32,
     pub line: u8,
-    pub framebuffer: [u32; 160 * 144],
+    pub framebuffer: [u32; SCREEN_WIDTH * SCREEN_HEIGHT],
     pub frame_ready: bool,
 }
 
@@ -28,7 +31,7 @@ impl PPU {
             mode: 2,
             mode_clock: 0,
             line: 0,
-            framebuffer: [0; 160 * 144],
+            framebuffer: [0; SCREEN_WIDTH * SCREEN_HEIGHT],
             frame_ready: false,
         }
     }
@@ -47,6 +50,7 @@ impl PPU {
         self.write_byte(0xFF0F, interrupt_bit | interrupt_flags);
     }
 
+    #[allow(clippy::collapsible_match)]
     pub fn step(&mut self, cycles: u32) {
         self.mode_clock += cycles;
 
@@ -97,21 +101,18 @@ impl PPU {
     }
 
     fn update_stat(&mut self) {
-        let mut stat = self.read_byte(Self::STAT_ADDR);
-
-        stat = (stat & 0xFC) | (self.mode & 0x03);
+        let stat = self.read_byte(Self::STAT_ADDR);
 
         let lyc = self.read_byte(Self::LYC_ADDR);
-        if self.line == lyc {
-            stat |= 0x04;
-            if stat & 0x40 != 0 {
-                self.add_interrupt(0x02);
-            }
-        } else {
-            stat &= !0x04;
+        let coincidence = self.line == lyc;
+        if coincidence && stat & 0x40 != 0 {
+            self.add_interrupt(0x02);
         }
 
-        self.write_byte(0xFF41, stat);
+        // bypasses the CPU write path, which keeps these bits read-only
+        self.memory
+            .borrow_mut()
+            .set_stat_mode(self.mode, coincidence);
     }
 
     fn render_scanline(&mut self) {
@@ -172,6 +173,7 @@ impl PPU {
         }
     }
 
+    #[allow(clippy::manual_range_contains)]
     fn render_sprites(&mut self) {
         let lcdc = self.read_byte(Self::LCDC_ADDR);
         let sprite_height = if lcdc & 0x04 != 0 { 16 } else { 8 };
@@ -300,17 +302,52 @@ impl PPU {
     }
     fn get_color(&self, color_num: u8, palette: u8) -> u32 {
         let shade = (palette >> (color_num * 2)) & 0x03;
-        let color = match shade {
+        match shade {
             0 => 0xFFFFFFFF,
             1 => 0xFFAAAAAA,
             2 => 0xFF555555,
             3 => 0xFF000000,
             _ => 0xFFFFFFFF,
-        };
-        println!(
-            "Color num: {}, Palette: 0x{:02X}, Shade: {}",
-            color_num, palette, shade
-        );
-        color
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::collections::BTreeSet;
+    use std::rc::Rc;
+
+    use super::PPU;
+    use crate::memory::Memory;
+
+    #[test]
+    fn stat_mode_advances_without_raising_interrupts() {
+        let memory = Rc::new(RefCell::new(Memory::new()));
+        memory.borrow_mut().write_byte(0xFF40, 0x91);
+        let mut ppu = PPU::new(memory.clone());
+
+        let mut modes = BTreeSet::new();
+        for _ in 0..20000 {
+            ppu.step(4);
+            let memory = memory.borrow();
+            modes.insert(memory.read_byte(0xFF41) & 0x03);
+            assert_eq!(memory.read_byte(0xFF0F) & 0x02, 0, "STAT interrupt raised");
+        }
+        assert_eq!(modes, BTreeSet::from([0, 1, 2, 3]));
+    }
+
+    #[test]
+    fn stat_coincidence_bit_follows_lyc() {
+        let memory = Rc::new(RefCell::new(Memory::new()));
+        memory.borrow_mut().write_byte(0xFF40, 0x91);
+        memory.borrow_mut().write_byte(0xFF45, 0x02);
+        let mut ppu = PPU::new(memory.clone());
+
+        while ppu.line != 2 {
+            assert_eq!(memory.borrow().read_byte(0xFF41) & 0x04, 0);
+            ppu.step(4);
+        }
+        assert_eq!(memory.borrow().read_byte(0xFF41) & 0x04, 0x04);
     }
 }
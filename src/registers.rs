@@ -46,6 +46,22 @@ impl Registers {
         }
     }
 
+    // state before a boot ROM runs; the boot ROM leaves the values new() starts from
+    pub fn power_on() -> Registers {
+        Registers {
+            a: 0,
+            f: 0,
+            b: 0,
+            c: 0,
+            d: 0,
+            e: 0,
+            h: 0,
+            l: 0,
+            sp: 0,
+            pc: 0x0000,
+        }
+    }
+
     pub fn get_register8(&self, reg: Register) -> u8 {
         match reg {
             Register::A => self.a,
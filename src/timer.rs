@@ -0,0 +1,220 @@
+/*
+0xFF04: DIV - upper 8 bits of the 16-bit internal divider, any write resets it.
+0xFF05: TIMA - timer counter, raises the timer interrupt on overflow.
+0xFF06: TMA - value loaded into TIMA after an overflow.
+0xFF07: TAC - bit 2 enables TIMA, bits 0-1 select the divider bit that clocks it.
+*/
+
+pub struct Timer {
+    pub div: u16,
+    pub tima: u8,
+    pub tma: u8,
+    pub tac: u8,
+    // TIMA overflowed during the last M-cycle and reads 0x00 until the reload
+    overflow: bool,
+    // TMA was copied into TIMA during the current M-cycle
+    reloading: bool,
+}
+
+impl Timer {
+    pub const DIV_ADDR: u16 = 0xFF04;
+    pub const TIMA_ADDR: u16 = 0xFF05;
+    pub const TMA_ADDR: u16 = 0xFF06;
+    pub const TAC_ADDR: u16 = 0xFF07;
+
+    pub fn new() -> Timer {
+        Timer {
+            div: 0,
+            tima: 0,
+            tma: 0,
+            tac: 0,
+            overflow: false,
+            reloading: false,
+        }
+    }
+
+    // divider bit that clocks TIMA for each TAC frequency: 4096, 262144, 65536, 16384 Hz
+    fn selected_bit(&self) -> u16 {
+        match self.tac & 0x03 {
+            0 => 1 << 9,
+            1 => 1 << 3,
+            2 => 1 << 5,
+            _ => 1 << 7,
+        }
+    }
+
+    // TIMA is clocked by the falling edge of (selected divider bit AND timer enable)
+    fn signal(&self) -> bool {
+        self.tac & 0x04 != 0 && self.div & self.selected_bit() != 0
+    }
+
+    fn increment_tima(&mut self) {
+        let (result, overflow) = self.tima.overflowing_add(1);
+        self.tima = result;
+        self.overflow = overflow;
+    }
+
+    // advances the timer by a number of T-cycles (always a multiple of 4),
+    // returns true if the timer interrupt should be requested
+    pub fn step(&mut self, cycles: u32) -> bool {
+        let mut interrupt = false;
+
+        for _ in 0..cycles / 4 {
+            interrupt |= self.tick();
+        }
+
+        interrupt
+    }
+
+    fn tick(&mut self) -> bool {
+        self.reloading = false;
+
+        let mut interrupt = false;
+        if self.overflow {
+            self.overflow = false;
+            self.tima = self.tma;
+            self.reloading = true;
+            interrupt = true;
+        }
+
+        let old_signal = self.signal();
+        self.div = self.div.wrapping_add(4);
+        if old_signal && !self.signal() {
+            self.increment_tima();
+        }
+
+        interrupt
+    }
+
+    pub fn read_byte(&self, addr: u16) -> u8 {
+        match addr {
+            Self::DIV_ADDR => (self.div >> 8) as u8,
+            Self::TIMA_ADDR => self.tima,
+            Self::TMA_ADDR => self.tma,
+            Self::TAC_ADDR => self.tac | 0xF8,
+            _ => 0xFF,
+        }
+    }
+
+    pub fn write_byte(&mut self, addr: u16, value: u8) {
+        match addr {
+            Self::DIV_ADDR => {
+                // resetting the divider can produce a falling edge on the selected bit
+                let old_signal = self.signal();
+                self.div = 0;
+                if old_signal {
+                    self.increment_tima();
+                }
+            }
+            // writes during the reload cycle are ignored, writes during the
+            // overflow cycle cancel the pending reload and interrupt
+            Self::TIMA_ADDR if !self.reloading => {
+                self.tima = value;
+                self.overflow = false;
+            }
+            Self::TMA_ADDR => {
+                self.tma = value;
+                if self.reloading {
+                    self.tima = value;
+                }
+            }
+            Self::TAC_ADDR => {
+                let old_signal = self.signal();
+                self.tac = value & 0x07;
+                if old_signal && !self.signal() {
+                    self.increment_tima();
+                }
+            }
+            _ => (),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Timer;
+
+    // TIMA enabled at 262144 Hz, one increment every 16 T-cycles
+    fn fast_timer() -> Timer {
+        let mut timer = Timer::new();
+        timer.write_byte(Timer::TAC_ADDR, 0x05);
+        timer
+    }
+
+    #[test]
+    fn div_counts_and_resets() {
+        let mut timer = Timer::new();
+        timer.step(256 * 3);
+        assert_eq!(timer.read_byte(Timer::DIV_ADDR), 3);
+        timer.write_byte(Timer::DIV_ADDR, 0x55);
+        assert_eq!(timer.read_byte(Timer::DIV_ADDR), 0);
+    }
+
+    #[test]
+    fn tima_follows_the_selected_frequency() {
+        for (tac, period) in [(0x04, 1024), (0x05, 16), (0x06, 64), (0x07, 256)] {
+            let mut timer = Timer::new();
+            timer.write_byte(Timer::TAC_ADDR, tac);
+            timer.step(period * 10);
+            assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 10, "TAC {:02X}", tac);
+        }
+
+        let mut timer = Timer::new();
+        timer.write_byte(Timer::TAC_ADDR, 0x01);
+        timer.step(1024);
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0);
+    }
+
+    #[test]
+    fn overflow_reloads_tma_one_cycle_late() {
+        let mut timer = fast_timer();
+        timer.write_byte(Timer::TMA_ADDR, 0xAB);
+        timer.write_byte(Timer::TIMA_ADDR, 0xFF);
+
+        assert!(!timer.step(16));
+        // TIMA reads 0x00 for the M-cycle before the reload
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0x00);
+        assert!(timer.step(4));
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0xAB);
+    }
+
+    #[test]
+    fn tima_write_during_overflow_cancels_the_reload() {
+        let mut timer = fast_timer();
+        timer.write_byte(Timer::TMA_ADDR, 0xAB);
+        timer.write_byte(Timer::TIMA_ADDR, 0xFF);
+        timer.step(16);
+
+        timer.write_byte(Timer::TIMA_ADDR, 0x12);
+        assert!(!timer.step(4));
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0x12);
+    }
+
+    #[test]
+    fn writes_during_the_reload_cycle() {
+        let mut timer = fast_timer();
+        timer.write_byte(Timer::TMA_ADDR, 0xAB);
+        timer.write_byte(Timer::TIMA_ADDR, 0xFF);
+        timer.step(20);
+
+        // TIMA writes are ignored, TMA writes go straight through to TIMA
+        timer.write_byte(Timer::TIMA_ADDR, 0x12);
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0xAB);
+        timer.write_byte(Timer::TMA_ADDR, 0x34);
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 0x34);
+    }
+
+    #[test]
+    fn falling_edges_from_div_and_tac_writes() {
+        let mut timer = fast_timer();
+        // divider bit 3 is set, so resetting DIV is a falling edge
+        timer.step(8);
+        timer.write_byte(Timer::DIV_ADDR, 0);
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 1);
+
+        timer.step(8);
+        timer.write_byte(Timer::TAC_ADDR, 0x00);
+        assert_eq!(timer.read_byte(Timer::TIMA_ADDR), 2);
+        assert_eq!(timer.read_byte(Timer::TAC_ADDR), 0xF8);
+    }
+}
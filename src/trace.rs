@@ -0,0 +1,75 @@
+use std::fs::File;
+use std::io::{self, BufWriter, Write};
+use std::ops::RangeInclusive;
+use std::path::Path;
+
+use crate::registers::Registers;
+
+/*
+Instruction trace in the Gameboy Doctor format, one line per instruction with the
+state before it runs:
+
+A:01 F:B0 B:00 C:13 D:00 E:D8 H:01 L:4D SP:FFFE PC:0100 PCMEM:00,C3,13,02
+
+PCMEM holds the four bytes at PC. Doctor's reference logs assume LY always reads
+0x90, so comparing against them needs doctor mode (--trace-doctor), which stubs LY.
+*/
+
+#[derive(Clone, Debug, Default)]
+pub struct TraceFilter {
+    // only log PCs inside one of these; empty logs everywhere
+    pub pc_ranges: Vec<RangeInclusive<u16>>,
+    // only log while PC is in one of these ROM banks (0 outside 0x4000 - 0x7FFF)
+    pub banks: Vec<u16>,
+    // stop the trace after this many lines
+    pub max_lines: Option<u64>,
+}
+
+impl TraceFilter {
+    pub fn matches(&self, pc: u16, bank: u16) -> bool {
+        (self.pc_ranges.is_empty() || self.pc_ranges.iter().any(|range| range.contains(&pc)))
+            && (self.banks.is_empty() || self.banks.contains(&bank))
+    }
+}
+
+pub struct Tracer {
+    writer: BufWriter<File>,
+    filter: TraceFilter,
+    lines: u64,
+}
+
+impl Tracer {
+    pub fn create(path: &Path, filter: TraceFilter) -> io::Result<Tracer> {
+        Ok(Tracer {
+            writer: BufWriter::new(File::create(path)?),
+            filter,
+            lines: 0,
+        })
+    }
+
+    // true once the line cap has been reached
+    pub fn is_done(&self) -> bool {
+        self.filter
+            .max_lines
+            .is_some_and(|max_lines| self.lines >= max_lines)
+    }
+
+    pub fn trace(&mut self, registers: &Registers, bank: u16, pcmem: [u8; 4]) -> io::Result<()> {
+        if self.is_done() || !self.filter.matches(registers.pc, bank) {
+            return Ok(());
+        }
+
+        let r = registers;
+        writeln!(
+            self.writer,
+            "A:{:02X} F:{:02X} B:{:02X} C:{:02X} D:{:02X} E:{:02X} H:{:02X} L:{:02X} SP:{:04X} PC:{:04X} PCMEM:{:02X},{:02X},{:02X},{:02X}",
+            r.a, r.f, r.b, r.c, r.d, r.e, r.h, r.l, r.sp, r.pc, pcmem[0], pcmem[1], pcmem[2], pcmem[3]
+        )?;
+        self.lines += 1;
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.writer.flush()
+    }
+}
@@ -0,0 +1,233 @@
+use std::{
+    fs::File,
+    io::{self, BufWriter, Seek, SeekFrom, Write},
+    path::{Path, PathBuf},
+};
+
+// the RIFF size field counts the data plus the 36 header bytes after it, in a u32
+const MAX_DATA_BYTES: u32 = u32::MAX - 36;
+
+// 16-bit PCM WAV writer; the RIFF and data sizes are patched in when finished
+pub struct WavWriter {
+    writer: BufWriter<File>,
+    channels: u16,
+    data_bytes: u32,
+    finished: bool,
+}
+
+impl WavWriter {
+    pub fn create(path: &Path, channels: u16, sample_rate: u32) -> io::Result<WavWriter> {
+        let mut writer = BufWriter::new(File::create(path)?);
+
+        let block_align = channels * 2;
+        writer.write_all(b"RIFF")?;
+        writer.write_all(&0u32.to_le_bytes())?; // patched in finish()
+        writer.write_all(b"WAVE")?;
+
+        writer.write_all(b"fmt ")?;
+        writer.write_all(&16u32.to_le_bytes())?;
+        writer.write_all(&1u16.to_le_bytes())?; // PCM
+        writer.write_all(&channels.to_le_bytes())?;
+        writer.write_all(&sample_rate.to_le_bytes())?;
+        writer.write_all(&(sample_rate * block_align as u32).to_le_bytes())?;
+        writer.write_all(&block_align.to_le_bytes())?;
+        writer.write_all(&16u16.to_le_bytes())?;
+
+        writer.write_all(b"data")?;
+        writer.write_all(&0u32.to_le_bytes())?; // patched in finish()
+
+        Ok(WavWriter {
+            writer,
+            channels,
+            data_bytes: 0,
+            finished: false,
+        })
+    }
+
+    // writes interleaved samples in [-1, 1]; fails without writing anything once the
+    // file would outgrow what the RIFF header can describe
+    pub fn write_samples(&mut self, samples: &[f32]) -> io::Result<()> {
+        u32::try_from(samples.len() * 2)
+            .ok()
+            .and_then(|bytes| self.data_bytes.checked_add(bytes))
+            .filter(|&data_bytes| data_bytes <= MAX_DATA_BYTES)
+            .ok_or_else(|| io::Error::other("WAV file reached the 4 GiB size limit"))?;
+
+        // counted per sample, so the header still matches the data if a write fails
+        for sample in samples {
+            let value = (sample.clamp(-1.0, 1.0) * i16::MAX as f32) as i16;
+            self.writer.write_all(&value.to_le_bytes())?;
+            self.data_bytes += 2;
+        }
+        Ok(())
+    }
+
+    pub fn channels(&self) -> u16 {
+        self.channels
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        if self.finished {
+            return Ok(());
+        }
+        self.finished = true;
+
+        self.writer.seek(SeekFrom::Start(4))?;
+        // can't overflow, write_samples keeps data_bytes within MAX_DATA_BYTES
+        self.writer
+            .write_all(&(36 + self.data_bytes).to_le_bytes())?;
+        self.writer.seek(SeekFrom::Start(40))?;
+        self.writer.write_all(&self.data_bytes.to_le_bytes())?;
+        self.writer.flush()
+    }
+}
+
+impl Drop for WavWriter {
+    fn drop(&mut self) {
+        let _ = self.finish();
+    }
+}
+
+// records the APU's stereo mix and, optionally, each channel as its own mono file
+pub struct AudioRecorder {
+    mix: WavWriter,
+    channels: Option<Vec<WavWriter>>,
+}
+
+impl AudioRecorder {
+    // per-channel files are written next to `path` as <name>_ch1.wav ... <name>_ch4.wav
+    pub fn create(path: &Path, per_channel: bool, sample_rate: u32) -> io::Result<AudioRecorder> {
+        let mix = WavWriter::create(path, 2, sample_rate)?;
+
+        let channels = if per_channel {
+            let mut writers = Vec::with_capacity(4);
+            for channel in 1..=4 {
+                writers.push(WavWriter::create(
+                    &AudioRecorder::channel_path(path, channel),
+                    1,
+                    sample_rate,
+                )?);
+            }
+            Some(writers)
+        } else {
+            None
+        };
+
+        Ok(AudioRecorder { mix, channels })
+    }
+
+    fn channel_path(path: &Path, channel: usize) -> PathBuf {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        path.with_file_name(format!("{}_ch{}.wav", stem, channel))
+    }
+
+    pub fn records_channels(&self) -> bool {
+        self.channels.is_some()
+    }
+
+    pub fn write(&mut self, left: f32, right: f32, channels: [f32; 4]) -> io::Result<()> {
+        self.mix.write_samples(&[left, right])?;
+        if let Some(writers) = self.channels.as_mut() {
+            for (writer, sample) in writers.iter_mut().zip(channels) {
+                writer.write_samples(&[sample])?;
+            }
+        }
+        Ok(())
+    }
+
+    pub fn finish(&mut self) -> io::Result<()> {
+        self.mix.finish()?;
+        if let Some(writers) = self.channels.as_mut() {
+            for writer in writers {
+                writer.finish()?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::fs;
+    use std::path::{Path, PathBuf};
+
+    use super::{AudioRecorder, WavWriter, MAX_DATA_BYTES};
+
+    fn temp_path(name: &str) -> PathBuf {
+        std::env::temp_dir().join(format!("lr35902-{}-{}.wav", std::process::id(), name))
+    }
+
+    fn le_u32(data: &[u8], offset: usize) -> u32 {
+        u32::from_le_bytes(data[offset..offset + 4].try_into().unwrap())
+    }
+
+    #[test]
+    fn header_sizes_are_patched_on_finish() {
+        let path = temp_path("header");
+        let mut wav = WavWriter::create(&path, 2, 65536).unwrap();
+        wav.write_samples(&[0.0, 1.0, -1.0, 2.0]).unwrap();
+        wav.finish().unwrap();
+
+        let data = fs::read(&path).unwrap();
+        fs::remove_file(&path).unwrap();
+        assert_eq!(data.len(), 44 + 8);
+        assert_eq!(&data[0..4], b"RIFF");
+        assert_eq!(le_u32(&data, 4), 36 + 8);
+        assert_eq!(le_u32(&data, 24), 65536);
+        assert_eq!(le_u32(&data, 28), 65536 * 4);
+        assert_eq!(le_u32(&data, 40), 8);
+
+        let samples: Vec<i16> = data[44..]
+            .chunks_exact(2)
+            .map(|pair| i16::from_le_bytes([pair[0], pair[1]]))
+            .collect();
+        // out of range samples are clamped
+        assert_eq!(samples, [0, i16::MAX, -i16::MAX, i16::MAX]);
+    }
+
+    #[test]
+    fn stops_at_the_riff_size_limit() {
+        let path = temp_path("limit");
+        let mut wav = WavWriter::create(&path, 2, 65536).unwrap();
+        wav.data_bytes = MAX_DATA_BYTES - 4;
+        wav.write_samples(&[0.0, 0.0]).unwrap();
+        assert!(wav.write_samples(&[0.0, 0.0]).is_err());
+        assert_eq!(wav.data_bytes, MAX_DATA_BYTES);
+
+        wav.data_bytes = u32::MAX - 1;
+        assert!(wav.write_samples(&[0.0, 0.0]).is_err());
+        wav.data_bytes = 0;
+        wav.finish().unwrap();
+        fs::remove_file(&path).unwrap();
+    }
+
+    #[test]
+    fn per_channel_files_sit_next_to_the_mix() {
+        let path = temp_path("channels");
+        let mut recorder = AudioRecorder::create(&path, true, 65536).unwrap();
+        assert!(recorder.records_channels());
+        recorder.write(0.5, -0.5, [0.1, 0.2, 0.3, 0.4]).unwrap();
+        recorder.finish().unwrap();
+
+        assert_eq!(fs::read(&path).unwrap().len(), 44 + 4);
+        fs::remove_file(&path).unwrap();
+        for channel in 1..=4 {
+            let channel_path = AudioRecorder::channel_path(&path, channel);
+            let data = fs::read(&channel_path).unwrap();
+            fs::remove_file(&channel_path).unwrap();
+            assert_eq!(data.len(), 44 + 2);
+            assert_eq!(u16::from_le_bytes([data[22], data[23]]), 1);
+        }
+    }
+
+    #[cfg(target_os = "linux")]
+    #[test]
+    fn failed_writes_only_count_what_was_written() {
+        // every write to /dev/full fails once the buffer has to be flushed
+        let mut wav = WavWriter::create(Path::new("/dev/full"), 1, 65536).unwrap();
+        let samples = vec![0.5; 16384];
+        assert!(wav.write_samples(&samples).is_err());
+        assert!(wav.data_bytes > 0 && wav.data_bytes < 2 * 16384);
+        assert_eq!(wav.data_bytes % 2, 0);
+    }
+}
@@ -0,0 +1,321 @@
+// runs every instruction against the community SM83 single-step test vectors
+// (https://github.com/SingleStepTests/sm83). each file, like `3e.json` or `cb 37.json`,
+// holds tests with an initial CPU + RAM state, the expected final state and the
+// bus activity for every M-cycle.
+//
+// the full suite isn't checked in: point SM83_TESTS at the `v1` directory of a checkout,
+// or put it at tests/sm83/v1, and run `cargo test --test sm83 -- --ignored`. a few
+// hand-written vectors in tests/fixtures/sm83 keep the harness itself running.
+
+#[path = "support/json.rs"]
+mod json;
+
+use std::cell::RefCell;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::rc::Rc;
+
+use json::Json;
+use lr35902_emulator::{bus::Bus, cpu::CPU, registers::Registers};
+
+const DEFAULT_TESTS_DIR: &str = "tests/sm83/v1";
+const FIXTURES_DIR: &str = "tests/fixtures/sm83";
+
+// STOP depends on the joypad, the timer and CGB speed switching, none of which
+// exist on a flat bus
+const SKIPPED: &[&str] = &["10"];
+
+// failures printed in full; the rest are only counted
+const MAX_REPORTED: usize = 50;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Access {
+    Read(u16, u8),
+    Write(u16, u8),
+}
+
+// flat 64 KiB of RAM, recording what happened on the bus in each M-cycle
+struct FlatMemory {
+    ram: Vec<u8>,
+    // None for an M-cycle without a memory access
+    cycles: Vec<Option<Access>>,
+}
+
+impl FlatMemory {
+    // the first access after a tick belongs to that M-cycle; later untimed accesses,
+    // like HALT peeking at IE and IF, aren't bus activity
+    fn record(&mut self, access: Access) {
+        match self.cycles.last_mut() {
+            Some(slot @ None) => *slot = Some(access),
+            Some(Some(_)) => (),
+            None => self.cycles.push(Some(access)),
+        }
+    }
+}
+
+struct TestBus {
+    memory: Rc<RefCell<FlatMemory>>,
+}
+
+impl Bus for TestBus {
+    fn read(&self, addr: u16) -> u8 {
+        let mut memory = self.memory.borrow_mut();
+        let value = memory.ram[addr as usize];
+        memory.record(Access::Read(addr, value));
+        value
+    }
+
+    fn write(&mut self, addr: u16, value: u8) {
+        let mut memory = self.memory.borrow_mut();
+        memory.ram[addr as usize] = value;
+        memory.record(Access::Write(addr, value));
+    }
+
+    fn tick(&mut self, cycles: u32) -> u32 {
+        let mut memory = self.memory.borrow_mut();
+        for _ in 0..cycles / 4 {
+            memory.cycles.push(None);
+        }
+        cycles
+    }
+}
+
+struct State {
+    registers: Registers,
+    ime: bool,
+    // EI has run but IME isn't set yet
+    ime_pending: Option<bool>,
+    ram: Vec<(u16, u8)>,
+}
+
+impl State {
+    fn parse(json: &Json) -> Result<State, String> {
+        let byte = |key: &str| field(json, key).map(|n| n as u8);
+
+        let ram = json
+            .get("ram")
+            .and_then(Json::as_array)
+            .ok_or("missing ram")?
+            .iter()
+            .map(|cell| match cell.as_array() {
+                Some([addr, value]) => match (addr.as_i64(), value.as_i64()) {
+                    (Some(addr), Some(value)) => Ok((addr as u16, value as u8)),
+                    _ => Err("bad ram cell".to_string()),
+                },
+                _ => Err("bad ram cell".to_string()),
+            })
+            .collect::<Result<Vec<_>, _>>()?;
+
+        Ok(State {
+            registers: Registers {
+                a: byte("a")?,
+                f: byte("f")?,
+                b: byte("b")?,
+                c: byte("c")?,
+                d: byte("d")?,
+                e: byte("e")?,
+                h: byte("h")?,
+                l: byte("l")?,
+                sp: field(json, "sp")? as u16,
+                pc: field(json, "pc")? as u16,
+            },
+            ime: field(json, "ime")? != 0,
+            ime_pending: json.get("ei").and_then(Json::as_i64).map(|ei| ei != 0),
+            ram,
+        })
+    }
+}
+
+fn field(json: &Json, key: &str) -> Result<i64, String> {
+    json.get(key)
+        .and_then(Json::as_i64)
+        .ok_or_else(|| format!("missing field '{}'", key))
+}
+
+// the expected bus activity; internal M-cycles are null or have no r/w in their kind
+fn parse_cycles(json: &Json) -> Result<Vec<Option<Access>>, String> {
+    let cycles = json
+        .get("cycles")
+        .and_then(Json::as_array)
+        .ok_or("missing cycles")?;
+    cycles
+        .iter()
+        .map(|cycle| {
+            let Some([addr, value, kind]) = cycle.as_array() else {
+                return Ok(None);
+            };
+            let kind = kind.as_str().unwrap_or("");
+            let (Some(addr), Some(value)) = (addr.as_i64(), value.as_i64()) else {
+                return Ok(None);
+            };
+            let (addr, value) = (addr as u16, value as u8);
+            Ok(if kind.contains('r') {
+                Some(Access::Read(addr, value))
+            } else if kind.contains('w') {
+                Some(Access::Write(addr, value))
+            } else {
+                None
+            })
+        })
+        .collect()
+}
+
+// runs one test, returning every difference from the expected outcome
+fn run_test(test: &Json) -> Result<Vec<String>, String> {
+    let initial = State::parse(test.get("initial").ok_or("missing initial")?)?;
+    let expected = State::parse(test.get("final").ok_or("missing final")?)?;
+    let expected_cycles = parse_cycles(test)?;
+
+    let memory = Rc::new(RefCell::new(FlatMemory {
+        ram: vec![0; 0x10000],
+        cycles: Vec::new(),
+    }));
+    for &(addr, value) in &initial.ram {
+        memory.borrow_mut().ram[addr as usize] = value;
+    }
+
+    let mut cpu = CPU::with_bus(Box::new(TestBus {
+        memory: memory.clone(),
+    }));
+    cpu.registers = initial.registers;
+    cpu.ime = initial.ime;
+    cpu.ime_pending = initial.ime_pending.unwrap_or(false);
+
+    let opcode = cpu.fetch_opcode();
+    cpu.execute_opcode(opcode);
+
+    let mut mismatches = Vec::new();
+    let mut check = |name: &str, expected: u16, actual: u16, width: usize| {
+        if expected != actual {
+            mismatches.push(format!(
+                "{}: expected {:0width$X}, got {:0width$X}",
+                name,
+                expected,
+                actual,
+                width = width
+            ));
+        }
+    };
+
+    let (want, got) = (&expected.registers, &cpu.registers);
+    check("A", want.a as u16, got.a as u16, 2);
+    check("B", want.b as u16, got.b as u16, 2);
+    check("C", want.c as u16, got.c as u16, 2);
+    check("D", want.d as u16, got.d as u16, 2);
+    check("E", want.e as u16, got.e as u16, 2);
+    check("H", want.h as u16, got.h as u16, 2);
+    check("L", want.l as u16, got.l as u16, 2);
+    check("SP", want.sp, got.sp, 4);
+    check("PC", want.pc, got.pc, 4);
+    for (bit, flag) in [(7, "Z"), (6, "N"), (5, "H"), (4, "C")] {
+        check(
+            &format!("flag {}", flag),
+            (want.f >> bit & 1) as u16,
+            (got.f >> bit & 1) as u16,
+            1,
+        );
+    }
+    check(
+        "F low bits",
+        (want.f & 0x0F) as u16,
+        (got.f & 0x0F) as u16,
+        1,
+    );
+    check("IME", expected.ime as u16, cpu.ime as u16, 1);
+    if let Some(ime_pending) = expected.ime_pending {
+        check("EI pending", ime_pending as u16, cpu.ime_pending as u16, 1);
+    }
+
+    let memory = memory.borrow();
+    for &(addr, value) in &expected.ram {
+        check(
+            &format!("RAM[{:04X}]", addr),
+            value as u16,
+            memory.ram[addr as usize] as u16,
+            2,
+        );
+    }
+
+    if memory.cycles.len() != expected_cycles.len() {
+        mismatches.push(format!(
+            "M-cycles: expected {}, got {}",
+            expected_cycles.len(),
+            memory.cycles.len()
+        ));
+    }
+    for (i, (want, got)) in expected_cycles.iter().zip(&memory.cycles).enumerate() {
+        if want != got {
+            mismatches.push(format!(
+                "M-cycle {}: expected {:X?}, got {:X?}",
+                i, want, got
+            ));
+        }
+    }
+
+    Ok(mismatches)
+}
+
+// runs every vector file in dir and fails with the first MAX_REPORTED differences
+fn run_dir(dir: &Path) {
+    let entries = fs::read_dir(dir)
+        .unwrap_or_else(|e| panic!("no SM83 test vectors at {}: {}", dir.display(), e));
+
+    let mut files: Vec<PathBuf> = entries
+        .filter_map(|entry| entry.ok().map(|entry| entry.path()))
+        .filter(|path| path.extension().is_some_and(|ext| ext == "json"))
+        .collect();
+    files.sort();
+    assert!(!files.is_empty(), "no .json files in {}", dir.display());
+
+    let mut tests_run = 0;
+    let mut failures = Vec::new();
+    for path in &files {
+        let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+        if SKIPPED.contains(&stem.as_ref()) {
+            continue;
+        }
+
+        let text = fs::read_to_string(path)
+            .unwrap_or_else(|e| panic!("failed to read {}: {}", path.display(), e));
+        let json = Json::parse(&text).unwrap_or_else(|e| panic!("{}: {}", path.display(), e));
+        let tests = json
+            .as_array()
+            .unwrap_or_else(|| panic!("{}: expected a list of tests", path.display()));
+
+        for test in tests {
+            let name = test.get("name").and_then(Json::as_str).unwrap_or("?");
+            let mismatches =
+                run_test(test).unwrap_or_else(|e| panic!("{} '{}': {}", path.display(), name, e));
+            tests_run += 1;
+            if !mismatches.is_empty() {
+                failures.push(format!("{}:\n    {}", name, mismatches.join("\n    ")));
+            }
+        }
+    }
+
+    for failure in failures.iter().take(MAX_REPORTED) {
+        eprintln!("{}", failure);
+    }
+    assert!(
+        failures.is_empty(),
+        "{} of {} SM83 tests failed ({} shown above)",
+        failures.len(),
+        tests_run,
+        failures.len().min(MAX_REPORTED)
+    );
+}
+
+#[test]
+fn sm83_fixtures() {
+    run_dir(&Path::new(env!("CARGO_MANIFEST_DIR")).join(FIXTURES_DIR));
+}
+
+#[test]
+#[ignore = "needs the SM83 test vectors, see the top of this file"]
+fn sm83_single_step() {
+    let dir = env::var_os("SM83_TESTS")
+        .map(PathBuf::from)
+        .unwrap_or_else(|| Path::new(env!("CARGO_MANIFEST_DIR")).join(DEFAULT_TESTS_DIR));
+    run_dir(&dir);
+}